@@ -0,0 +1,250 @@
+use std::collections::VecDeque;
+use crate::direction::Direction;
+use crate::position::Position;
+use crate::simulation::Simulation;
+use crate::wall_mode::WallMode;
+
+pub const MAX_LEVEL: u32 = 5;
+const MISTAKE_CHANCE_PER_LEVEL: f64 = 0.12;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AiStrategy {
+    Greedy,
+    Bfs,
+    TailChaser,
+}
+
+impl AiStrategy {
+    pub fn next(self) -> AiStrategy {
+        match self {
+            AiStrategy::Greedy => AiStrategy::Bfs,
+            AiStrategy::Bfs => AiStrategy::TailChaser,
+            AiStrategy::TailChaser => AiStrategy::Greedy,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AiStrategy::Greedy => "Ochko'z",
+            AiStrategy::Bfs => "Eng qisqa yo'l",
+            AiStrategy::TailChaser => "Ehtiyotkor",
+        }
+    }
+}
+
+pub struct AiController {
+    pub strategy: AiStrategy,
+    pub level: u32,
+    rng: fastrand::Rng,
+}
+
+impl AiController {
+    pub fn new(strategy: AiStrategy, level: u32, seed: u64) -> Self {
+        Self {
+            strategy,
+            level: level.clamp(1, MAX_LEVEL),
+            rng: fastrand::Rng::with_seed(seed),
+        }
+    }
+
+    pub fn choose(&mut self, simulation: &Simulation, index: usize) -> Option<Direction> {
+        let mistake_chance = (MAX_LEVEL - self.level) as f64 * MISTAKE_CHANCE_PER_LEVEL;
+        if self.rng.f64() < mistake_chance {
+            return None;
+        }
+
+        match self.strategy {
+            AiStrategy::Greedy => greedy(simulation, index),
+            AiStrategy::Bfs => shortest_path(simulation, index),
+            AiStrategy::TailChaser => tail_chaser(simulation, index),
+        }
+    }
+}
+
+pub struct Board {
+    width: i32,
+    height: i32,
+    wrap: bool,
+    blocked: Vec<bool>,
+}
+
+impl Board {
+    pub fn new(simulation: &Simulation, index: usize, cautious: bool) -> Self {
+        let mut board = Board {
+            width: simulation.grid_width(),
+            height: simulation.grid_height(),
+            wrap: simulation.wall_mode() == WallMode::Wrap,
+            blocked: vec![false; (simulation.grid_width() * simulation.grid_height()) as usize],
+        };
+
+        for &wall in simulation.walls() {
+            board.block(wall);
+        }
+        for (other, snake) in simulation.snakes().iter().enumerate() {
+            if !snake.alive {
+                continue;
+            }
+            for &pos in &snake.body {
+                board.block(pos);
+            }
+            if cautious && other != index {
+                for direction in Direction::ALL {
+                    if let Some(pos) = board.neighbor(snake.head(), direction) {
+                        board.block(pos);
+                    }
+                }
+            }
+        }
+
+        board
+    }
+
+    pub fn block(&mut self, pos: Position) {
+        let index = self.index(pos);
+        self.blocked[index] = true;
+    }
+
+    pub fn is_blocked(&self, pos: Position) -> bool {
+        self.blocked[self.index(pos)]
+    }
+
+    pub fn neighbor(&self, pos: Position, direction: Direction) -> Option<Position> {
+        let next = pos.moved(direction);
+        if self.wrap {
+            return Some(Position {
+                x: next.x.rem_euclid(self.width),
+                y: next.y.rem_euclid(self.height),
+            });
+        }
+        let inside = next.x >= 0 && next.x < self.width && next.y >= 0 && next.y < self.height;
+        inside.then_some(next)
+    }
+
+    pub fn safe_moves(&self, pos: Position, heading: Direction) -> Vec<(Direction, Position)> {
+        Direction::ALL
+            .into_iter()
+            .filter(|&direction| !heading.is_opposite(direction))
+            .filter_map(|direction| self.neighbor(pos, direction).map(|next| (direction, next)))
+            .filter(|&(_, next)| !self.is_blocked(next))
+            .collect()
+    }
+
+    pub fn first_step_to(&self, start: Position, heading: Direction, is_target: impl Fn(Position) -> bool) -> Option<Direction> {
+        let mut visited = vec![false; self.blocked.len()];
+        let mut queue = VecDeque::new();
+        visited[self.index(start)] = true;
+
+        for (direction, next) in self.safe_moves(start, heading) {
+            visited[self.index(next)] = true;
+            queue.push_back((next, direction));
+        }
+
+        while let Some((pos, first)) = queue.pop_front() {
+            if is_target(pos) {
+                return Some(first);
+            }
+            for direction in Direction::ALL {
+                if let Some(next) = self.neighbor(pos, direction)
+                    && !self.is_blocked(next)
+                    && !visited[self.index(next)] {
+                    visited[self.index(next)] = true;
+                    queue.push_back((next, first));
+                }
+            }
+        }
+
+        None
+    }
+
+    pub fn reachable_area(&self, start: Position) -> usize {
+        let mut visited = vec![false; self.blocked.len()];
+        let mut stack = vec![start];
+        visited[self.index(start)] = true;
+        let mut area = 0;
+
+        while let Some(pos) = stack.pop() {
+            area += 1;
+            for direction in Direction::ALL {
+                if let Some(next) = self.neighbor(pos, direction)
+                    && !self.is_blocked(next)
+                    && !visited[self.index(next)] {
+                    visited[self.index(next)] = true;
+                    stack.push(next);
+                }
+            }
+        }
+
+        area
+    }
+
+    fn index(&self, pos: Position) -> usize {
+        (pos.y * self.width + pos.x) as usize
+    }
+}
+
+fn greedy(simulation: &Simulation, index: usize) -> Option<Direction> {
+    let snake = &simulation.snakes()[index];
+    let board = Board::new(simulation, index, false);
+
+    board
+        .safe_moves(snake.head(), snake.direction)
+        .into_iter()
+        .min_by_key(|&(_, next)| {
+            simulation
+                .foods()
+                .iter()
+                .map(|food| (food.position.x - next.x).abs() + (food.position.y - next.y).abs())
+                .min()
+                .unwrap_or(0)
+        })
+        .map(|(direction, _)| direction)
+}
+
+fn shortest_path(simulation: &Simulation, index: usize) -> Option<Direction> {
+    let snake = &simulation.snakes()[index];
+    let board = Board::new(simulation, index, true);
+    let is_food = |pos: Position| simulation.foods().iter().any(|food| food.position == pos);
+
+    board
+        .first_step_to(snake.head(), snake.direction, is_food)
+        .or_else(|| roomiest_move(simulation, index))
+}
+
+pub fn tail_chaser(simulation: &Simulation, index: usize) -> Option<Direction> {
+    let snake = &simulation.snakes()[index];
+    let board = Board::new(simulation, index, true);
+    let tail = snake.body[0];
+    let near_tail = |pos: Position| {
+        Direction::ALL
+            .into_iter()
+            .any(|direction| board.neighbor(tail, direction) == Some(pos))
+    };
+    let is_food = |pos: Position| simulation.foods().iter().any(|food| food.position == pos);
+
+    if let Some(direction) = board.first_step_to(snake.head(), snake.direction, is_food)
+        && let Some(next) = board.neighbor(snake.head(), direction) {
+        let mut after = Board::new(simulation, index, false);
+        after.block(next);
+        if snake.body.len() == 1 || after.first_step_to(next, direction, near_tail).is_some() {
+            return Some(direction);
+        }
+    }
+
+    if snake.body.len() > 1
+        && let Some(direction) = board.first_step_to(snake.head(), snake.direction, near_tail) {
+        return Some(direction);
+    }
+
+    roomiest_move(simulation, index)
+}
+
+pub fn roomiest_move(simulation: &Simulation, index: usize) -> Option<Direction> {
+    let snake = &simulation.snakes()[index];
+    let board = Board::new(simulation, index, false);
+
+    board
+        .safe_moves(snake.head(), snake.direction)
+        .into_iter()
+        .max_by_key(|&(_, next)| board.reachable_area(next))
+        .map(|(direction, _)| direction)
+}
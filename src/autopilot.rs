@@ -0,0 +1,100 @@
+use crate::ai::{self, Board};
+use crate::direction::Direction;
+use crate::position::Position;
+use crate::simulation::Simulation;
+
+const SAFETY_GAP: usize = 4;
+
+pub struct Autopilot {
+    width: i32,
+    order: Option<Vec<usize>>,
+}
+
+impl Autopilot {
+    pub fn new(simulation: &Simulation) -> Self {
+        let width = simulation.grid_width();
+        let order = if simulation.walls().is_empty() {
+            hamiltonian_cycle(width, simulation.grid_height()).map(|cycle| {
+                let mut order = vec![0; cycle.len()];
+                for (ordinal, pos) in cycle.iter().enumerate() {
+                    order[(pos.y * width + pos.x) as usize] = ordinal;
+                }
+                order
+            })
+        } else {
+            None
+        };
+
+        Self { width, order }
+    }
+
+    pub fn choose(&self, simulation: &Simulation, index: usize) -> Option<Direction> {
+        let Some(order) = &self.order else {
+            return ai::tail_chaser(simulation, index);
+        };
+
+        let snake = &simulation.snakes()[index];
+        let cells = order.len();
+        let ordinal = |pos: Position| order[(pos.y * self.width + pos.x) as usize];
+        let distance = |from: Position, to: Position| (ordinal(to) + cells - ordinal(from)) % cells;
+
+        let head = snake.head();
+        let body_distance = snake.body[..snake.body.len() - 1]
+            .iter()
+            .map(|&segment| distance(head, segment))
+            .filter(|&distance| distance > 0)
+            .min()
+            .unwrap_or(cells);
+        let food_distance = simulation
+            .foods()
+            .iter()
+            .map(|food| distance(head, food.position))
+            .min()
+            .unwrap_or(1);
+
+        let max_jump = if snake.body.len() * 2 < cells {
+            food_distance.min(body_distance.saturating_sub(snake.body.len() + SAFETY_GAP)).max(1)
+        } else {
+            1
+        };
+
+        let board = Board::new(simulation, index, false);
+        board
+            .safe_moves(head, snake.direction)
+            .into_iter()
+            .map(|(direction, next)| (direction, distance(head, next)))
+            .filter(|&(_, jump)| jump >= 1 && jump <= max_jump)
+            .max_by_key(|&(_, jump)| jump)
+            .map(|(direction, _)| direction)
+            .or_else(|| ai::roomiest_move(simulation, index))
+    }
+}
+
+fn hamiltonian_cycle(width: i32, height: i32) -> Option<Vec<Position>> {
+    if width < 2 || height < 2 {
+        return None;
+    }
+    if height % 2 != 0 {
+        let transposed = hamiltonian_cycle_even_rows(height, width)?;
+        return Some(transposed.into_iter().map(|pos| Position { x: pos.y, y: pos.x }).collect());
+    }
+    hamiltonian_cycle_even_rows(width, height)
+}
+
+fn hamiltonian_cycle_even_rows(width: i32, height: i32) -> Option<Vec<Position>> {
+    if height % 2 != 0 {
+        return None;
+    }
+
+    let mut cycle = Vec::with_capacity((width * height) as usize);
+    for y in 0..height {
+        if y % 2 == 0 {
+            cycle.extend((1..width).map(|x| Position { x, y }));
+        } else {
+            cycle.extend((1..width).rev().map(|x| Position { x, y }));
+        }
+    }
+    cycle.extend((0..height).rev().map(|y| Position { x: 0, y }));
+
+    Some(cycle)
+}
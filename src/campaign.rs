@@ -0,0 +1,85 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use crate::simulation::Simulation;
+use crate::storage;
+use crate::wall_mode::WallMode;
+
+#[derive(Debug, Clone, Copy)]
+pub enum StageGoal {
+    Length(usize),
+    Score(i32),
+}
+
+impl StageGoal {
+    pub fn is_met(self, simulation: &Simulation) -> bool {
+        match self {
+            StageGoal::Length(length) => simulation.player().body.len() >= length,
+            StageGoal::Score(score) => simulation.player().score >= score,
+        }
+    }
+
+    pub fn label(self) -> String {
+        match self {
+            StageGoal::Length(length) => format!("uzunlik {}", length),
+            StageGoal::Score(score) => format!("{} ball", score),
+        }
+    }
+}
+
+pub struct Stage {
+    pub level: &'static str,
+    pub wall_mode: WallMode,
+    pub goal: StageGoal,
+}
+
+pub const STAGES: [Stage; 6] = [
+    Stage { level: "Ochiq maydon", wall_mode: WallMode::Solid, goal: StageGoal::Length(10) },
+    Stage { level: "Quti", wall_mode: WallMode::Solid, goal: StageGoal::Score(150) },
+    Stage { level: "Xoch", wall_mode: WallMode::Wrap, goal: StageGoal::Length(20) },
+    Stage { level: "Ustunlar", wall_mode: WallMode::Solid, goal: StageGoal::Score(300) },
+    Stage { level: "Yo'laklar", wall_mode: WallMode::Solid, goal: StageGoal::Length(30) },
+    Stage { level: "Ustunlar", wall_mode: WallMode::Wrap, goal: StageGoal::Score(500) },
+];
+
+pub struct CampaignProgress {
+    pub unlocked: usize,
+}
+
+impl CampaignProgress {
+    pub fn load() -> Self {
+        let unlocked = fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|contents| {
+                contents
+                    .lines()
+                    .find_map(|line| line.strip_prefix("unlocked="))
+                    .and_then(|value| value.trim().parse().ok())
+            })
+            .unwrap_or(0);
+
+        Self { unlocked: usize::min(unlocked, STAGES.len() - 1) }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, format!("unlocked={}\n", self.unlocked))
+    }
+
+    pub fn unlock(&mut self, stage: usize) -> bool {
+        let stage = stage.min(STAGES.len() - 1);
+        if stage > self.unlocked {
+            self.unlocked = stage;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn path() -> PathBuf {
+        storage::data_dir().join("campaign.txt")
+    }
+}
@@ -0,0 +1,110 @@
+use std::path::PathBuf;
+
+const CONFIG_FLAGS: [&str; 12] = [
+    "grid_width",
+    "grid_height",
+    "cell_size",
+    "move_interval",
+    "speed_multiplier",
+    "min_move_interval",
+    "bonus_food_chance",
+    "shrink_food_chance",
+    "slow_food_chance",
+    "powerup_chance",
+    "min_swipe_distance",
+    "input_max_age",
+];
+
+pub struct CliArgs {
+    pub seed: Option<u64>,
+    pub level: Option<PathBuf>,
+    pub config: Option<PathBuf>,
+    pub simulate: Option<usize>,
+    pub overrides: Vec<(String, String)>,
+}
+
+impl CliArgs {
+    pub fn parse() -> Result<Self, String> {
+        Self::parse_from(std::env::args().skip(1))
+    }
+
+    pub fn parse_from<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+        let mut cli = CliArgs {
+            seed: None,
+            level: None,
+            config: None,
+            simulate: None,
+            overrides: Vec::new(),
+        };
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--seed" => {
+                    let value = args.next().ok_or("--seed uchun qiymat kerak")?;
+                    let seed = value
+                        .parse()
+                        .map_err(|_| format!("noto'g'ri seed: {}", value))?;
+                    cli.seed = Some(seed);
+                }
+                "--level" => {
+                    let value = args.next().ok_or("--level uchun fayl yo'li kerak")?;
+                    cli.level = Some(PathBuf::from(value));
+                }
+                "--config" => {
+                    let value = args.next().ok_or("--config uchun fayl yo'li kerak")?;
+                    cli.config = Some(PathBuf::from(value));
+                }
+                "--simulate" => {
+                    let value = args.next().ok_or("--simulate uchun o'yinlar soni kerak")?;
+                    let games = value
+                        .parse()
+                        .map_err(|_| format!("noto'g'ri o'yinlar soni: {}", value))?;
+                    cli.simulate = Some(games);
+                }
+                _ => {
+                    let key = arg
+                        .strip_prefix("--")
+                        .map(|flag| flag.replace('-', "_"))
+                        .filter(|key| CONFIG_FLAGS.contains(&key.as_str()))
+                        .ok_or_else(|| format!("noma'lum argument: {}", arg))?;
+                    let value = args.next().ok_or_else(|| format!("{} uchun qiymat kerak", arg))?;
+                    cli.overrides.push((key, value));
+                }
+            }
+        }
+
+        Ok(cli)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<CliArgs, String> {
+        CliArgs::parse_from(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parses_flags_and_config_overrides() {
+        let cli = parse(&["--seed", "42", "--config", "my.txt", "--grid-width", "30", "--move_interval", "0.2"])
+            .expect("arguments parse");
+
+        assert_eq!(cli.seed, Some(42));
+        assert_eq!(cli.config, Some(PathBuf::from("my.txt")));
+        assert_eq!(
+            cli.overrides,
+            [("grid_width".to_string(), "30".to_string()), ("move_interval".to_string(), "0.2".to_string())]
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_flags_and_missing_values() {
+        assert!(parse(&["--speed", "3"]).is_err());
+        assert!(parse(&["grid_width", "30"]).is_err());
+        assert!(parse(&["--seed"]).is_err());
+        assert!(parse(&["--seed", "abc"]).is_err());
+        assert!(parse(&["--grid-width"]).is_err());
+    }
+}
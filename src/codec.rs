@@ -0,0 +1,134 @@
+use crate::difficulty::Difficulty;
+use crate::direction::Direction;
+use crate::food::FoodKind;
+use crate::position::Position;
+use crate::powerup::PowerUpKind;
+use crate::wall_mode::WallMode;
+
+#[derive(Debug)]
+pub struct Truncated;
+
+pub struct ByteReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> ByteReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    pub fn remaining(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    pub fn take(&mut self, len: usize) -> Result<&'a [u8], Truncated> {
+        if self.bytes.len() < len {
+            return Err(Truncated);
+        }
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    pub fn u8(&mut self) -> Result<u8, Truncated> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub fn u16(&mut self) -> Result<u16, Truncated> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    pub fn u32(&mut self) -> Result<u32, Truncated> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    pub fn i32(&mut self) -> Result<i32, Truncated> {
+        Ok(i32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    pub fn u64(&mut self) -> Result<u64, Truncated> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    pub fn f64(&mut self) -> Result<f64, Truncated> {
+        Ok(f64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    pub fn position(&mut self) -> Result<Position, Truncated> {
+        Ok(Position { x: self.i32()?, y: self.i32()? })
+    }
+}
+
+pub fn put_position(bytes: &mut Vec<u8>, pos: Position) {
+    bytes.extend_from_slice(&pos.x.to_le_bytes());
+    bytes.extend_from_slice(&pos.y.to_le_bytes());
+}
+
+pub fn direction_to_byte(direction: Direction) -> u8 {
+    match direction {
+        Direction::Up => 0,
+        Direction::Down => 1,
+        Direction::Left => 2,
+        Direction::Right => 3,
+    }
+}
+
+pub fn direction_from_byte(value: u8) -> Option<Direction> {
+    match value {
+        0 => Some(Direction::Up),
+        1 => Some(Direction::Down),
+        2 => Some(Direction::Left),
+        3 => Some(Direction::Right),
+        _ => None,
+    }
+}
+
+pub fn wall_mode_to_byte(wall_mode: WallMode) -> u8 {
+    match wall_mode {
+        WallMode::Solid => 0,
+        WallMode::Wrap => 1,
+    }
+}
+
+pub fn wall_mode_from_byte(value: u8) -> Option<WallMode> {
+    match value {
+        0 => Some(WallMode::Solid),
+        1 => Some(WallMode::Wrap),
+        _ => None,
+    }
+}
+
+pub fn difficulty_to_byte(difficulty: Difficulty) -> u8 {
+    Difficulty::ALL.iter().position(|&d| d == difficulty).unwrap_or(1) as u8
+}
+
+pub fn difficulty_from_byte(value: u8) -> Option<Difficulty> {
+    Difficulty::ALL.get(value as usize).copied()
+}
+
+pub fn food_kind_to_byte(kind: FoodKind) -> u8 {
+    match kind {
+        FoodKind::Normal => 0,
+        FoodKind::Bonus => 1,
+        FoodKind::Shrink => 2,
+        FoodKind::Slow => 3,
+    }
+}
+
+pub fn food_kind_from_byte(value: u8) -> Option<FoodKind> {
+    match value {
+        0 => Some(FoodKind::Normal),
+        1 => Some(FoodKind::Bonus),
+        2 => Some(FoodKind::Shrink),
+        3 => Some(FoodKind::Slow),
+        _ => None,
+    }
+}
+
+pub fn powerup_kind_to_byte(kind: PowerUpKind) -> u8 {
+    PowerUpKind::ALL.iter().position(|&k| k == kind).unwrap_or(0) as u8
+}
+
+pub fn powerup_kind_from_byte(value: u8) -> Option<PowerUpKind> {
+    PowerUpKind::ALL.get(value as usize).copied()
+}
@@ -0,0 +1,297 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use crate::simulation::Rules;
+use crate::difficulty::Difficulty;
+use crate::food::{FoodChances, SLOW_FACTOR};
+use crate::storage;
+use crate::wall_mode::WallMode;
+
+pub const GRID_SIZE: RangeInclusive<i32> = 5..=200;
+
+#[derive(Debug, Clone)]
+pub struct GameConfig {
+    pub grid_width: i32,
+    pub grid_height: i32,
+    pub cell_size: f32,
+    pub move_interval: f64,
+    pub speed_multiplier: f64,
+    pub min_move_interval: f64,
+    pub bonus_food_chance: f64,
+    pub shrink_food_chance: f64,
+    pub slow_food_chance: f64,
+    pub powerup_chance: f64,
+    pub min_swipe_distance: f32,
+    pub input_max_age: f64,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(PathBuf, io::Error),
+    Syntax { line: usize },
+    UnknownKey { line: Option<usize>, key: String },
+    InvalidValue { line: Option<usize>, key: String, value: String },
+    Invalid(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let at = |line: &Option<usize>| line.map(|line| format!("{}-qator: ", line)).unwrap_or_default();
+        match self {
+            ConfigError::Io(path, err) => write!(f, "{}: {}", path.display(), err),
+            ConfigError::Syntax { line } => write!(f, "{}-qator: 'kalit = qiymat' ko'rinishida bo'lishi kerak", line),
+            ConfigError::UnknownKey { line, key } => write!(f, "{}noma'lum sozlama: {}", at(line), key),
+            ConfigError::InvalidValue { line, key, value } => {
+                write!(f, "{}{} uchun noto'g'ri qiymat: {}", at(line), key, value)
+            }
+            ConfigError::Invalid(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            grid_width: 25,
+            grid_height: 20,
+            cell_size: 25.0,
+            move_interval: 0.12,
+            speed_multiplier: 0.97,
+            min_move_interval: 0.04,
+            bonus_food_chance: 0.15,
+            shrink_food_chance: 0.08,
+            slow_food_chance: 0.08,
+            powerup_chance: 0.1,
+            min_swipe_distance: 30.0,
+            input_max_age: 1.0,
+        }
+    }
+}
+
+impl GameConfig {
+    pub fn default_path() -> PathBuf {
+        storage::data_dir().join("config.txt")
+    }
+
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let contents = fs::read_to_string(path).map_err(|err| ConfigError::Io(path.to_path_buf(), err))?;
+        let mut config = GameConfig::default();
+
+        for (index, line) in contents.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or(ConfigError::Syntax { line: line_number })?;
+            config.set(key.trim(), value.trim(), Some(line_number))?;
+        }
+
+        Ok(config)
+    }
+
+    pub fn set(&mut self, key: &str, value: &str, line: Option<usize>) -> Result<(), ConfigError> {
+        let invalid = || ConfigError::InvalidValue {
+            line,
+            key: key.to_string(),
+            value: value.to_string(),
+        };
+
+        match key {
+            "grid_width" => self.grid_width = value.parse().map_err(|_| invalid())?,
+            "grid_height" => self.grid_height = value.parse().map_err(|_| invalid())?,
+            "cell_size" => self.cell_size = value.parse().map_err(|_| invalid())?,
+            "move_interval" => self.move_interval = value.parse().map_err(|_| invalid())?,
+            "speed_multiplier" => self.speed_multiplier = value.parse().map_err(|_| invalid())?,
+            "min_move_interval" => self.min_move_interval = value.parse().map_err(|_| invalid())?,
+            "bonus_food_chance" => self.bonus_food_chance = value.parse().map_err(|_| invalid())?,
+            "shrink_food_chance" => self.shrink_food_chance = value.parse().map_err(|_| invalid())?,
+            "slow_food_chance" => self.slow_food_chance = value.parse().map_err(|_| invalid())?,
+            "powerup_chance" => self.powerup_chance = value.parse().map_err(|_| invalid())?,
+            "min_swipe_distance" => self.min_swipe_distance = value.parse().map_err(|_| invalid())?,
+            "input_max_age" => self.input_max_age = value.parse().map_err(|_| invalid())?,
+            _ => return Err(ConfigError::UnknownKey { line, key: key.to_string() }),
+        }
+
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if !GRID_SIZE.contains(&self.grid_width) || !GRID_SIZE.contains(&self.grid_height) {
+            return Err(ConfigError::Invalid(format!(
+                "maydon o'lchami 5 dan 200 gacha bo'lishi kerak, berilgan: {}x{}",
+                self.grid_width, self.grid_height
+            )));
+        }
+        if !(4.0..=100.0).contains(&self.cell_size) {
+            return Err(ConfigError::Invalid(format!(
+                "cell_size 4 dan 100 gacha bo'lishi kerak, berilgan: {}",
+                self.cell_size
+            )));
+        }
+        if !(0.01..=2.0).contains(&self.move_interval) {
+            return Err(ConfigError::Invalid(format!(
+                "move_interval 0.01 dan 2.0 gacha bo'lishi kerak, berilgan: {}",
+                self.move_interval
+            )));
+        }
+        if !(self.speed_multiplier > 0.0 && self.speed_multiplier <= 1.0) {
+            return Err(ConfigError::Invalid(format!(
+                "speed_multiplier 0 dan katta va 1 dan oshmasligi kerak, berilgan: {}",
+                self.speed_multiplier
+            )));
+        }
+        if !(self.min_move_interval >= 0.01 && self.min_move_interval <= self.move_interval) {
+            return Err(ConfigError::Invalid(format!(
+                "min_move_interval 0.01 dan move_interval ({}) gacha bo'lishi kerak, berilgan: {}",
+                self.move_interval, self.min_move_interval
+            )));
+        }
+        if !(5.0..=400.0).contains(&self.min_swipe_distance) {
+            return Err(ConfigError::Invalid(format!(
+                "min_swipe_distance 5 dan 400 gacha bo'lishi kerak, berilgan: {}",
+                self.min_swipe_distance
+            )));
+        }
+        if !(0.05..=5.0).contains(&self.input_max_age) {
+            return Err(ConfigError::Invalid(format!(
+                "input_max_age 0.05 dan 5.0 gacha bo'lishi kerak, berilgan: {}",
+                self.input_max_age
+            )));
+        }
+        let slowest_interval = Difficulty::ALL
+            .iter()
+            .map(|difficulty| self.move_interval * difficulty.interval_scale() * SLOW_FACTOR)
+            .fold(0.0, f64::max);
+        if self.input_max_age < slowest_interval {
+            return Err(ConfigError::Invalid(format!(
+                "input_max_age eng sekin qadamdan ({:.2}) qisqa bo'lmasligi kerak, berilgan: {}",
+                slowest_interval, self.input_max_age
+            )));
+        }
+        let chances = [
+            ("bonus_food_chance", self.bonus_food_chance),
+            ("shrink_food_chance", self.shrink_food_chance),
+            ("slow_food_chance", self.slow_food_chance),
+            ("powerup_chance", self.powerup_chance),
+        ];
+        for (key, chance) in chances {
+            if !(0.0..=1.0).contains(&chance) {
+                return Err(ConfigError::Invalid(format!(
+                    "{} 0 dan 1 gacha bo'lishi kerak, berilgan: {}",
+                    key, chance
+                )));
+            }
+        }
+        if self.food_chances().total() > 1.0 {
+            return Err(ConfigError::Invalid(format!(
+                "maxsus ovqat ehtimollari yig'indisi 1 dan oshmasligi kerak, berilgan: {}",
+                self.food_chances().total()
+            )));
+        }
+
+        Ok(())
+    }
+
+    pub fn food_chances(&self) -> FoodChances {
+        FoodChances {
+            bonus: self.bonus_food_chance,
+            shrink: self.shrink_food_chance,
+            slow: self.slow_food_chance,
+        }
+    }
+
+    pub fn rules(&self, wall_mode: WallMode) -> Rules {
+        Rules {
+            wall_mode,
+            move_interval: self.move_interval,
+            speed_multiplier: self.speed_multiplier,
+            min_move_interval: self.min_move_interval,
+            food_score: 10,
+            food_chances: self.food_chances(),
+            powerup_chance: self.powerup_chance,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with(key: &str, value: &str) -> GameConfig {
+        let mut config = GameConfig::default();
+        config.set(key, value, None).expect("known key");
+        config
+    }
+
+    #[test]
+    fn default_config_is_valid() {
+        assert!(GameConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn rejects_out_of_range_values() {
+        let cases = [
+            ("grid_width", "4"),
+            ("grid_height", "201"),
+            ("cell_size", "3"),
+            ("move_interval", "0"),
+            ("speed_multiplier", "1.5"),
+            ("speed_multiplier", "0"),
+            ("min_move_interval", "0.5"),
+            ("min_swipe_distance", "1000"),
+            ("input_max_age", "10"),
+            ("bonus_food_chance", "-0.1"),
+            ("powerup_chance", "1.5"),
+            ("bonus_food_chance", "0.9"),
+        ];
+        for (key, value) in cases {
+            assert!(
+                matches!(with(key, value).validate(), Err(ConfigError::Invalid(_))),
+                "{} = {} should be rejected",
+                key,
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn load_reads_keys_and_reports_syntax_errors() {
+        let path = std::env::temp_dir().join(format!("ilon-config-test-{}.txt", std::process::id()));
+        fs::write(&path, "# izoh\ngrid_width = 30\n\nmove_interval=0.2\n").unwrap();
+        let config = GameConfig::load(&path).expect("config loads");
+        assert_eq!((config.grid_width, config.move_interval), (30, 0.2));
+
+        fs::write(&path, "grid_width = 30\nkeng\n").unwrap();
+        let result = GameConfig::load(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(result, Err(ConfigError::Syntax { line: 2 })));
+    }
+
+    #[test]
+    fn reports_bad_lines() {
+        let mut config = GameConfig::default();
+        assert!(matches!(
+            config.set("grid_width", "keng", Some(3)),
+            Err(ConfigError::InvalidValue { line: Some(3), .. })
+        ));
+        assert!(matches!(
+            config.set("grid_depth", "10", Some(4)),
+            Err(ConfigError::UnknownKey { line: Some(4), .. })
+        ));
+    }
+
+    #[test]
+    fn input_max_age_must_outlast_the_slowest_step() {
+        let config = GameConfig { move_interval: 0.5, input_max_age: 0.9, ..GameConfig::default() };
+        assert!(matches!(config.validate(), Err(ConfigError::Invalid(_))));
+
+        let config = GameConfig { move_interval: 0.5, input_max_age: 1.1, ..GameConfig::default() };
+        assert!(config.validate().is_ok());
+    }
+}
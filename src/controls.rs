@@ -0,0 +1,549 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use macroquad::prelude::{is_key_down, is_key_pressed, KeyCode};
+use crate::config::ConfigError;
+use crate::direction::Direction;
+use crate::storage;
+
+pub const KEYS_PER_ACTION: usize = 2;
+pub const SCHEME_PLAYERS: usize = 2;
+
+const SCHEME_KEYS: [&str; SCHEME_PLAYERS] = ["player1_scheme", "player2_scheme"];
+
+const BINDABLE_KEYS: [KeyCode; 76] = [
+    KeyCode::A, KeyCode::B, KeyCode::C, KeyCode::D, KeyCode::E, KeyCode::F, KeyCode::G,
+    KeyCode::H, KeyCode::I, KeyCode::J, KeyCode::K, KeyCode::L, KeyCode::M, KeyCode::N,
+    KeyCode::O, KeyCode::P, KeyCode::Q, KeyCode::R, KeyCode::S, KeyCode::T, KeyCode::U,
+    KeyCode::V, KeyCode::W, KeyCode::X, KeyCode::Y, KeyCode::Z,
+    KeyCode::Key0, KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4,
+    KeyCode::Key5, KeyCode::Key6, KeyCode::Key7, KeyCode::Key8, KeyCode::Key9,
+    KeyCode::Kp0, KeyCode::Kp1, KeyCode::Kp2, KeyCode::Kp3, KeyCode::Kp4,
+    KeyCode::Kp5, KeyCode::Kp6, KeyCode::Kp7, KeyCode::Kp8, KeyCode::Kp9,
+    KeyCode::KpEnter, KeyCode::KpAdd, KeyCode::KpSubtract,
+    KeyCode::Up, KeyCode::Down, KeyCode::Left, KeyCode::Right,
+    KeyCode::Space, KeyCode::Enter, KeyCode::Escape, KeyCode::Backspace, KeyCode::Tab,
+    KeyCode::Insert, KeyCode::Home, KeyCode::End, KeyCode::PageUp, KeyCode::PageDown,
+    KeyCode::LeftShift, KeyCode::RightShift, KeyCode::LeftControl, KeyCode::RightControl,
+    KeyCode::LeftAlt, KeyCode::RightAlt,
+    KeyCode::Comma, KeyCode::Period, KeyCode::Slash, KeyCode::Semicolon,
+    KeyCode::Apostrophe, KeyCode::LeftBracket, KeyCode::RightBracket,
+];
+
+const SEED_KEYS: [KeyCode; 21] = [
+    KeyCode::Backspace,
+    KeyCode::Key0, KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4,
+    KeyCode::Key5, KeyCode::Key6, KeyCode::Key7, KeyCode::Key8, KeyCode::Key9,
+    KeyCode::Kp0, KeyCode::Kp1, KeyCode::Kp2, KeyCode::Kp3, KeyCode::Kp4,
+    KeyCode::Kp5, KeyCode::Kp6, KeyCode::Kp7, KeyCode::Kp8, KeyCode::Kp9,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    TurnUp,
+    TurnDown,
+    TurnLeft,
+    TurnRight,
+    AltTurnUp,
+    AltTurnDown,
+    AltTurnLeft,
+    AltTurnRight,
+    Pause,
+    Autopilot,
+    Rewind,
+    Confirm,
+    Back,
+    SaveAndQuit,
+    WatchReplay,
+    Continue,
+    OpenCampaign,
+    OpenLeaderboard,
+    OpenControls,
+    OpenOpponents,
+    LastReplay,
+    TogglePractice,
+    ToggleWallMode,
+    CycleDifficulty,
+    NextLevel,
+    ToggleVersus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Context {
+    Play,
+    Paused,
+    Menu,
+    Screens,
+}
+
+impl Action {
+    pub const ALL: [Action; 26] = [
+        Action::TurnUp,
+        Action::TurnDown,
+        Action::TurnLeft,
+        Action::TurnRight,
+        Action::AltTurnUp,
+        Action::AltTurnDown,
+        Action::AltTurnLeft,
+        Action::AltTurnRight,
+        Action::Pause,
+        Action::Autopilot,
+        Action::Rewind,
+        Action::Confirm,
+        Action::Back,
+        Action::SaveAndQuit,
+        Action::WatchReplay,
+        Action::Continue,
+        Action::OpenCampaign,
+        Action::OpenLeaderboard,
+        Action::OpenControls,
+        Action::OpenOpponents,
+        Action::LastReplay,
+        Action::TogglePractice,
+        Action::ToggleWallMode,
+        Action::CycleDifficulty,
+        Action::NextLevel,
+        Action::ToggleVersus,
+    ];
+
+    pub fn id(self) -> &'static str {
+        match self {
+            Action::TurnUp => "turn_up",
+            Action::TurnDown => "turn_down",
+            Action::TurnLeft => "turn_left",
+            Action::TurnRight => "turn_right",
+            Action::AltTurnUp => "alt_turn_up",
+            Action::AltTurnDown => "alt_turn_down",
+            Action::AltTurnLeft => "alt_turn_left",
+            Action::AltTurnRight => "alt_turn_right",
+            Action::Pause => "pause",
+            Action::Autopilot => "autopilot",
+            Action::Rewind => "rewind",
+            Action::Confirm => "confirm",
+            Action::Back => "back",
+            Action::SaveAndQuit => "save_and_quit",
+            Action::WatchReplay => "watch_replay",
+            Action::Continue => "continue",
+            Action::OpenCampaign => "campaign",
+            Action::OpenLeaderboard => "leaderboard",
+            Action::OpenControls => "controls",
+            Action::OpenOpponents => "opponents",
+            Action::LastReplay => "last_replay",
+            Action::TogglePractice => "practice",
+            Action::ToggleWallMode => "wall_mode",
+            Action::CycleDifficulty => "difficulty",
+            Action::NextLevel => "level",
+            Action::ToggleVersus => "versus",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::TurnUp => "Yuqoriga (1-o'yinchi)",
+            Action::TurnDown => "Pastga (1-o'yinchi)",
+            Action::TurnLeft => "Chapga (1-o'yinchi)",
+            Action::TurnRight => "O'ngga (1-o'yinchi)",
+            Action::AltTurnUp => "Yuqoriga (2-o'yinchi)",
+            Action::AltTurnDown => "Pastga (2-o'yinchi)",
+            Action::AltTurnLeft => "Chapga (2-o'yinchi)",
+            Action::AltTurnRight => "O'ngga (2-o'yinchi)",
+            Action::Pause => "Pauza",
+            Action::Autopilot => "Avtopilot",
+            Action::Rewind => "Orqaga qaytarish",
+            Action::Confirm => "Tasdiqlash",
+            Action::Back => "Orqaga / menyu",
+            Action::SaveAndQuit => "Saqlash va chiqish",
+            Action::WatchReplay => "Takrorni ko'rish",
+            Action::Continue => "Saqlangan o'yinni davom ettirish",
+            Action::OpenCampaign => "Kampaniya",
+            Action::OpenLeaderboard => "Rekordlar jadvali",
+            Action::OpenControls => "Boshqaruv sozlamalari",
+            Action::OpenOpponents => "Raqiblar",
+            Action::LastReplay => "Oxirgi takror",
+            Action::TogglePractice => "Mashq rejimi",
+            Action::ToggleWallMode => "Devor rejimi",
+            Action::CycleDifficulty => "Qiyinlik",
+            Action::NextLevel => "Xarita",
+            Action::ToggleVersus => "Ikki o'yinchi",
+        }
+    }
+
+    fn turns(alt: bool) -> [(Action, Direction); 4] {
+        if alt {
+            [
+                (Action::AltTurnUp, Direction::Up),
+                (Action::AltTurnDown, Direction::Down),
+                (Action::AltTurnLeft, Direction::Left),
+                (Action::AltTurnRight, Direction::Right),
+            ]
+        } else {
+            [
+                (Action::TurnUp, Direction::Up),
+                (Action::TurnDown, Direction::Down),
+                (Action::TurnLeft, Direction::Left),
+                (Action::TurnRight, Direction::Right),
+            ]
+        }
+    }
+
+    fn contexts(self) -> &'static [Context] {
+        match self {
+            Action::TurnUp
+            | Action::TurnDown
+            | Action::TurnLeft
+            | Action::TurnRight
+            | Action::AltTurnUp
+            | Action::AltTurnDown
+            | Action::AltTurnLeft
+            | Action::AltTurnRight
+            | Action::Rewind => &[Context::Play, Context::Screens],
+            Action::Pause => &[Context::Play, Context::Paused],
+            Action::Autopilot => &[Context::Play],
+            Action::Confirm => &[Context::Menu, Context::Screens],
+            Action::Back | Action::WatchReplay => &[Context::Screens],
+            Action::SaveAndQuit => &[Context::Paused],
+            Action::NextLevel => &[Context::Menu, Context::Screens],
+            _ => &[Context::Menu],
+        }
+    }
+
+    fn reserves(self, key: KeyCode) -> bool {
+        self.contexts().contains(&Context::Menu) && SEED_KEYS.contains(&key)
+    }
+
+    fn conflicts_with(self, other: Action) -> bool {
+        self.contexts().iter().any(|context| other.contexts().contains(context))
+    }
+
+    fn default_keys(self) -> Vec<KeyCode> {
+        match self {
+            Action::TurnUp => vec![KeyCode::W],
+            Action::TurnDown => vec![KeyCode::S],
+            Action::TurnLeft => vec![KeyCode::A],
+            Action::TurnRight => vec![KeyCode::D],
+            Action::AltTurnUp => vec![KeyCode::Up],
+            Action::AltTurnDown => vec![KeyCode::Down],
+            Action::AltTurnLeft => vec![KeyCode::Left],
+            Action::AltTurnRight => vec![KeyCode::Right],
+            Action::Pause => vec![KeyCode::Escape],
+            Action::Autopilot => vec![KeyCode::P],
+            Action::Rewind => vec![KeyCode::Backspace],
+            Action::Confirm => vec![KeyCode::Space, KeyCode::Enter],
+            Action::Back => vec![KeyCode::Escape],
+            Action::SaveAndQuit => vec![KeyCode::S],
+            Action::WatchReplay => vec![KeyCode::R],
+            Action::Continue => vec![KeyCode::D],
+            Action::OpenCampaign => vec![KeyCode::C],
+            Action::OpenLeaderboard => vec![KeyCode::T],
+            Action::OpenControls => vec![KeyCode::B],
+            Action::OpenOpponents => vec![KeyCode::O],
+            Action::LastReplay => vec![KeyCode::L],
+            Action::TogglePractice => vec![KeyCode::R],
+            Action::ToggleWallMode => vec![KeyCode::M],
+            Action::CycleDifficulty => vec![KeyCode::Q],
+            Action::NextLevel => vec![KeyCode::K],
+            Action::ToggleVersus => vec![KeyCode::V],
+        }
+    }
+
+    fn from_id(id: &str) -> Option<Action> {
+        Action::ALL.into_iter().find(|action| action.id() == id)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlScheme {
+    Absolute,
+    Relative,
+}
+
+impl ControlScheme {
+    pub fn toggled(self) -> ControlScheme {
+        match self {
+            ControlScheme::Absolute => ControlScheme::Relative,
+            ControlScheme::Relative => ControlScheme::Absolute,
+        }
+    }
+
+    pub fn id(self) -> &'static str {
+        match self {
+            ControlScheme::Absolute => "absolute",
+            ControlScheme::Relative => "relative",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ControlScheme::Absolute => "mutlaq",
+            ControlScheme::Relative => "nisbiy (chapga/o'ngga burilish)",
+        }
+    }
+
+    pub fn resolve(self, input: Direction, heading: Direction) -> Option<Direction> {
+        match (self, input) {
+            (ControlScheme::Absolute, _) => Some(input),
+            (ControlScheme::Relative, Direction::Left) => Some(heading.turned_left()),
+            (ControlScheme::Relative, Direction::Right) => Some(heading.turned_right()),
+            (ControlScheme::Relative, _) => None,
+        }
+    }
+
+    fn from_id(id: &str) -> Option<ControlScheme> {
+        [ControlScheme::Absolute, ControlScheme::Relative].into_iter().find(|scheme| scheme.id() == id)
+    }
+}
+
+pub fn key_name(key: KeyCode) -> String {
+    format!("{:?}", key)
+}
+
+fn key_from_name(name: &str) -> Option<KeyCode> {
+    BINDABLE_KEYS.into_iter().find(|&key| key_name(key).eq_ignore_ascii_case(name))
+}
+
+pub fn is_bindable(action: Action, key: KeyCode) -> bool {
+    BINDABLE_KEYS.contains(&key) && !action.reserves(key)
+}
+
+#[derive(Debug, Clone)]
+pub struct Controls {
+    bindings: HashMap<Action, Vec<KeyCode>>,
+    schemes: [ControlScheme; SCHEME_PLAYERS],
+}
+
+impl Default for Controls {
+    fn default() -> Self {
+        Self {
+            bindings: Action::ALL.into_iter().map(|action| (action, action.default_keys())).collect(),
+            schemes: [ControlScheme::Absolute; SCHEME_PLAYERS],
+        }
+    }
+}
+
+impl Controls {
+    pub fn path() -> PathBuf {
+        storage::data_dir().join("controls.txt")
+    }
+
+    pub fn load() -> Result<Self, ConfigError> {
+        let path = Self::path();
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => return Err(ConfigError::Io(path, err)),
+        };
+        Self::parse(&contents)
+    }
+
+    fn parse(contents: &str) -> Result<Self, ConfigError> {
+        let mut controls = Self::default();
+        for (index, line) in contents.lines().enumerate() {
+            let line_number = Some(index + 1);
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, value) = line.split_once('=').ok_or(ConfigError::Syntax { line: index + 1 })?;
+            let (key, value) = (key.trim(), value.trim());
+            let invalid = || ConfigError::InvalidValue {
+                line: line_number,
+                key: key.to_string(),
+                value: value.to_string(),
+            };
+            if let Some(player) = SCHEME_KEYS.iter().position(|&id| id == key) {
+                controls.schemes[player] = ControlScheme::from_id(value).ok_or_else(invalid)?;
+                continue;
+            }
+
+            let action = Action::from_id(key).ok_or_else(|| ConfigError::UnknownKey {
+                line: line_number,
+                key: key.to_string(),
+            })?;
+            let keys: Option<Vec<KeyCode>> = value
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(key_from_name)
+                .collect();
+            let keys = keys
+                .filter(|keys| (1..=KEYS_PER_ACTION).contains(&keys.len()))
+                .filter(|keys| keys.iter().all(|&key| !action.reserves(key)))
+                .ok_or_else(invalid)?;
+            controls.bindings.insert(action, keys);
+        }
+
+        for action in Action::ALL {
+            for &key in controls.keys(action) {
+                if let Some(other) = controls.conflict(action, key) {
+                    return Err(ConfigError::Invalid(format!(
+                        "{} tugmasi ham '{}', ham '{}' uchun belgilangan",
+                        key_name(key),
+                        action.id(),
+                        other.id()
+                    )));
+                }
+            }
+        }
+
+        Ok(controls)
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut contents = String::new();
+        for action in Action::ALL {
+            let keys: Vec<String> = self.keys(action).iter().map(|&key| key_name(key)).collect();
+            contents.push_str(&format!("{} = {}\n", action.id(), keys.join(", ")));
+        }
+        for (key, scheme) in SCHEME_KEYS.iter().zip(self.schemes) {
+            contents.push_str(&format!("{} = {}\n", key, scheme.id()));
+        }
+        fs::write(path, contents)
+    }
+
+    pub fn scheme(&self, player: usize) -> ControlScheme {
+        self.schemes.get(player).copied().unwrap_or(ControlScheme::Absolute)
+    }
+
+    pub fn toggle_scheme(&mut self, player: usize) {
+        if let Some(scheme) = self.schemes.get_mut(player) {
+            *scheme = scheme.toggled();
+        }
+    }
+
+    pub fn keys(&self, action: Action) -> &[KeyCode] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn hint(&self, action: Action) -> String {
+        self.keys(action).first().map_or_else(|| "-".to_string(), |&key| key_name(key))
+    }
+
+    pub fn turn_hint(&self, alt: bool) -> String {
+        let [up, down, left, right] = Action::turns(alt).map(|(action, _)| self.hint(action));
+        format!("{}/{}/{}/{}", up, left, down, right)
+    }
+
+    pub fn pressed(&self, action: Action) -> bool {
+        self.keys(action).iter().any(|&key| is_key_pressed(key))
+    }
+
+    pub fn down(&self, action: Action) -> bool {
+        self.keys(action).iter().any(|&key| is_key_down(key))
+    }
+
+    pub fn pressed_turn(&self, alt: bool) -> Option<Direction> {
+        Action::turns(alt)
+            .into_iter()
+            .find(|&(action, _)| self.pressed(action))
+            .map(|(_, direction)| direction)
+    }
+
+    pub fn pressed_any_turn(&self) -> Option<Direction> {
+        self.pressed_turn(false).or_else(|| self.pressed_turn(true))
+    }
+
+    pub fn conflict(&self, action: Action, key: KeyCode) -> Option<Action> {
+        Action::ALL
+            .into_iter()
+            .filter(|&other| other != action && action.conflicts_with(other))
+            .find(|&other| self.keys(other).contains(&key))
+    }
+
+    pub fn bind(&mut self, action: Action, slot: usize, key: KeyCode) -> Result<(), Action> {
+        if let Some(other) = self.conflict(action, key) {
+            return Err(other);
+        }
+
+        let keys = self.bindings.entry(action).or_default();
+        keys.retain(|&bound| bound != key);
+        if slot < keys.len() {
+            keys[slot] = key;
+        } else if keys.len() < KEYS_PER_ACTION {
+            keys.push(key);
+        }
+        Ok(())
+    }
+
+    pub fn unbind(&mut self, action: Action, slot: usize) -> bool {
+        let keys = self.bindings.entry(action).or_default();
+        if slot < keys.len() && keys.len() > 1 {
+            keys.remove(slot);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seed_keys_cannot_be_bound_to_menu_actions() {
+        assert!(!is_bindable(Action::OpenCampaign, KeyCode::Key5));
+        assert!(!is_bindable(Action::Continue, KeyCode::Backspace));
+        assert!(is_bindable(Action::Rewind, KeyCode::Backspace));
+        assert!(is_bindable(Action::OpenCampaign, KeyCode::G));
+    }
+    #[test]
+    fn same_context_keys_conflict() {
+        let mut controls = Controls::default();
+        assert_eq!(controls.bind(Action::OpenCampaign, 0, KeyCode::Q), Err(Action::CycleDifficulty));
+        assert_eq!(controls.bind(Action::Pause, 0, KeyCode::P), Err(Action::Autopilot));
+        assert_eq!(controls.keys(Action::OpenCampaign), [KeyCode::C]);
+
+        assert_eq!(controls.bind(Action::OpenCampaign, 1, KeyCode::G), Ok(()));
+        assert_eq!(controls.keys(Action::OpenCampaign), [KeyCode::C, KeyCode::G]);
+    }
+
+    #[test]
+    fn keys_are_shared_across_contexts() {
+        let mut controls = Controls::default();
+        assert_eq!(controls.conflict(Action::Pause, KeyCode::Escape), None);
+        assert_eq!(controls.conflict(Action::TogglePractice, KeyCode::R), None);
+        assert_eq!(controls.bind(Action::SaveAndQuit, 0, KeyCode::W), Ok(()));
+        assert_eq!(controls.bind(Action::Autopilot, 0, KeyCode::C), Ok(()));
+    }
+
+    #[test]
+    fn unlisted_actions_keep_their_defaults() {
+        let controls = Controls::parse("# izoh\npause = F\nconfirm = Enter, space\nplayer2_scheme = relative\n")
+            .expect("controls parse");
+        assert_eq!(controls.keys(Action::Pause), [KeyCode::F]);
+        assert_eq!(controls.keys(Action::Confirm), [KeyCode::Enter, KeyCode::Space]);
+        assert_eq!(controls.scheme(1), ControlScheme::Relative);
+
+        let defaults = Controls::default();
+        for action in Action::ALL.into_iter().filter(|&action| action != Action::Pause && action != Action::Confirm) {
+            assert_eq!(controls.keys(action), defaults.keys(action));
+        }
+        assert_eq!(controls.scheme(0), ControlScheme::Absolute);
+    }
+
+    #[test]
+    fn invalid_files_are_rejected() {
+        assert!(Controls::parse("pause = P\nautopilot = P\n").is_err());
+        assert!(Controls::parse("pause = F13\n").is_err());
+        assert!(Controls::parse("pause = A, B, C\n").is_err());
+        assert!(Controls::parse("jump = Space\n").is_err());
+        assert!(Controls::parse("campaign = Key1\n").is_err());
+    }
+
+    #[test]
+    fn relative_scheme_turns_from_the_heading() {
+        assert_eq!(ControlScheme::Absolute.resolve(Direction::Down, Direction::Up), Some(Direction::Down));
+        assert_eq!(ControlScheme::Relative.resolve(Direction::Left, Direction::Up), Some(Direction::Left));
+        assert_eq!(ControlScheme::Relative.resolve(Direction::Left, Direction::Right), Some(Direction::Up));
+        assert_eq!(ControlScheme::Relative.resolve(Direction::Right, Direction::Down), Some(Direction::Left));
+        assert_eq!(ControlScheme::Relative.resolve(Direction::Up, Direction::Left), None);
+        assert_eq!(ControlScheme::Relative.resolve(Direction::Down, Direction::Left), None);
+    }
+}
@@ -0,0 +1,114 @@
+use crate::config::GameConfig;
+use crate::simulation::Rules;
+use crate::wall_mode::WallMode;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+    Insane,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Normal,
+        Difficulty::Hard,
+        Difficulty::Insane,
+    ];
+
+    pub fn next(self) -> Difficulty {
+        match self {
+            Difficulty::Easy => Difficulty::Normal,
+            Difficulty::Normal => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Insane,
+            Difficulty::Insane => Difficulty::Easy,
+        }
+    }
+
+    pub fn id(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+            Difficulty::Insane => "insane",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Oson",
+            Difficulty::Normal => "Oddiy",
+            Difficulty::Hard => "Qiyin",
+            Difficulty::Insane => "Ekstrem",
+        }
+    }
+
+    pub fn wall_mode(self) -> WallMode {
+        match self {
+            Difficulty::Easy => WallMode::Wrap,
+            _ => WallMode::Solid,
+        }
+    }
+
+    pub fn buffer_size(self) -> usize {
+        match self {
+            Difficulty::Easy => 4,
+            Difficulty::Normal => 3,
+            Difficulty::Hard => 2,
+            Difficulty::Insane => 1,
+        }
+    }
+
+    pub fn interval_scale(self) -> f64 {
+        match self {
+            Difficulty::Easy => 4.0 / 3.0,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 0.75,
+            Difficulty::Insane => 0.5,
+        }
+    }
+
+    pub fn rules(self, config: &GameConfig, wall_mode: WallMode) -> Rules {
+        let base = config.rules(wall_mode);
+        let (speedup_scale, score_scale) = match self {
+            Difficulty::Easy => (0.5, 0.5),
+            Difficulty::Normal => (1.0, 1.0),
+            Difficulty::Hard => (4.0 / 3.0, 1.5),
+            Difficulty::Insane => (5.0 / 3.0, 2.5),
+        };
+        let move_interval = base.move_interval * self.interval_scale();
+
+        Rules {
+            move_interval,
+            speed_multiplier: base.speed_multiplier.powf(speedup_scale),
+            min_move_interval: base.min_move_interval.min(move_interval),
+            food_score: (base.food_score as f64 * score_scale).round() as i32,
+            ..base
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_scale_the_configured_speed() {
+        let config = GameConfig { move_interval: 0.2, speed_multiplier: 0.9, ..GameConfig::default() };
+        let normal = Difficulty::Normal.rules(&config, WallMode::Solid);
+        assert_eq!(normal.move_interval, 0.2);
+        assert_eq!(normal.speed_multiplier, 0.9);
+
+        let mut previous = Difficulty::Easy.rules(&config, WallMode::Solid);
+        for difficulty in &Difficulty::ALL[1..] {
+            let rules = difficulty.rules(&config, WallMode::Solid);
+            assert!(rules.move_interval < previous.move_interval);
+            assert!(rules.speed_multiplier < previous.speed_multiplier);
+            assert!(rules.food_score > previous.food_score);
+            previous = rules;
+        }
+        assert_eq!(Difficulty::Insane.rules(&config, WallMode::Solid).move_interval, 0.1);
+    }
+}
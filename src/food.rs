@@ -0,0 +1,57 @@
+use crate::position::Position;
+
+pub const BONUS_MULTIPLIER: i32 = 5;
+pub const SHRINK_AMOUNT: usize = 3;
+pub const SLOW_TICKS: u32 = 50;
+pub const SLOW_FACTOR: f64 = 1.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FoodKind {
+    Normal,
+    Bonus,
+    Shrink,
+    Slow,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Food {
+    pub position: Position,
+    pub kind: FoodKind,
+    pub expires_at: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FoodChances {
+    pub bonus: f64,
+    pub shrink: f64,
+    pub slow: f64,
+}
+
+impl FoodKind {
+    pub fn lifetime(self) -> Option<u32> {
+        match self {
+            FoodKind::Normal => None,
+            FoodKind::Bonus => Some(40),
+            FoodKind::Shrink => Some(80),
+            FoodKind::Slow => Some(60),
+        }
+    }
+}
+
+impl FoodChances {
+    pub fn total(&self) -> f64 {
+        self.bonus + self.shrink + self.slow
+    }
+
+    pub fn pick(&self, roll: f64) -> Option<FoodKind> {
+        if roll < self.bonus {
+            Some(FoodKind::Bonus)
+        } else if roll < self.bonus + self.shrink {
+            Some(FoodKind::Shrink)
+        } else if roll < self.total() {
+            Some(FoodKind::Slow)
+        } else {
+            None
+        }
+    }
+}
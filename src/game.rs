@@ -1,257 +1,1068 @@
+use std::collections::HashMap;
 use macroquad::prelude::*;
-use crate::position::Position;
 use crate::direction::Direction;
+use crate::position::Position;
 use crate::game_state::GameState;
 use crate::vec2_pool::Vec2Pool;
-use crate::spatial_hash::SpatialHash;
 use crate::texture_cache::TextureCache;
 use crate::input_buffer::InputBuffer;
+use crate::simulation::{SimEvent, Simulation, Snake, VICTORY_BONUS};
+use crate::replay::{Replay, ReplayPlayer};
+use crate::storage;
+use crate::wall_mode::WallMode;
+use crate::level::{Level, DEFAULT_LEVEL};
+use crate::config::GameConfig;
+use crate::difficulty::Difficulty;
+use crate::food::FoodKind;
+use crate::campaign::{CampaignProgress, STAGES};
+use crate::leaderboard::{self, Leaderboard, LeaderboardEntry, NAME_LENGTH};
+use crate::ai::{self, AiController, AiStrategy};
+use crate::autopilot::Autopilot;
+use crate::savegame::SaveGame;
+use crate::rewind::{RewindBuffer, REWIND_CAPACITY, REWIND_INTERVAL};
+use crate::controls::{self, Action, Controls, KEYS_PER_ACTION, SCHEME_PLAYERS};
+use crate::gamepad::Gamepads;
+use crate::pointer::{self, Gesture, Pointer};
+use crate::ui::{self, Button};
+
+const MAX_FRAME_TIME: f64 = 0.25;
+const VERSUS_PLAYERS: usize = 2;
+const MAX_OPPONENTS: usize = 3;
+const ATTRACT_DELAY: f64 = 5.0;
+const BOARD_MARGIN_X: f32 = 40.0;
+const BOARD_MARGIN_Y: f32 = 140.0;
+const MIN_WINDOW: (f32, f32) = (800.0, 600.0);
+const MAX_WINDOW: (f32, f32) = (1600.0, 1000.0);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum UiAction {
+    Start,
+    Continue,
+    Campaign,
+    Leaderboard,
+    Controls,
+    Retry,
+    WatchReplay,
+    Exit,
+}
 
 pub struct Game {
-    snake: Vec<Position>,
-    snake_positions: Vec2Pool,
-    food: Position,
-    direction: Direction,
-    grid_width: i32,
-    grid_height: i32,
+    config: GameConfig,
+    simulation: Simulation,
+    snake_positions: Vec<Vec2Pool>,
     cell_size: f32,
-    score: i32,
     state: GameState,
-    last_move_time: f64,
-    move_interval: f64,
-    high_score: i32,
+    accumulator: f64,
+    previous_bodies: Vec<Vec<Position>>,
+    high_scores: HashMap<String, i32>,
+    leaderboard: Leaderboard,
+    leaderboard_mode: WallMode,
+    leaderboard_difficulty: Difficulty,
+    leaderboard_level: usize,
+    name_input: String,
+    name_entry_return: GameState,
+    run_time: f64,
+    wall_mode: WallMode,
+    difficulty: Difficulty,
+    run_difficulty: Difficulty,
+    run_level: String,
+    levels: Vec<Level>,
+    level_index: usize,
+    campaign_progress: CampaignProgress,
+    selected_stage: usize,
+    current_stage: Option<usize>,
+    versus: bool,
+    versus_wins: [u32; VERSUS_PLAYERS],
+    round_winner: Option<usize>,
+    opponents: usize,
+    ai_strategy: AiStrategy,
+    ai_level: u32,
+    ai_controllers: Vec<AiController>,
+    opponent_row: usize,
+    autopilot: Option<Autopilot>,
+    autopilot_used: bool,
+    practice: bool,
+    rewind: Option<RewindBuffer>,
+    has_save: bool,
+    controls: Controls,
+    controls_row: usize,
+    controls_slot: usize,
+    rebinding: bool,
+    gamepads: Gamepads,
+    pointer: Pointer,
+    menu_idle: f64,
+    demo: Option<Autopilot>,
     animation_progress: f32,
+    seed_input: String,
+    message: Option<String>,
 
-    spatial_hash: SpatialHash,
     texture_cache: TextureCache,
-    input_buffer: InputBuffer,
+    input_buffers: Vec<InputBuffer>,
+    tick: u32,
+    recording: Replay,
+    last_replay: Option<Replay>,
+    replay_player: Option<ReplayPlayer>,
 
     _max_snake_length: usize,
     _draw_params_cache: Vec<DrawTextureParams>,
 }
 
 impl Game {
-    pub fn new() -> Self {
-        let grid_width = 25;
-        let grid_height = 20;
-        let cell_size = 25.0;
-        let max_snake_length = (grid_width * grid_height) as usize;
-
-        let mut snake = Vec::with_capacity(max_snake_length);
-        snake.push(Position { x: grid_width / 2, y: grid_height / 2 });
-
-        let mut snake_positions = Vec2Pool::new(max_snake_length);
-        snake_positions.get((grid_width / 2) as f32, (grid_height / 2) as f32);
+    pub fn new(config: GameConfig, seed: Option<u64>, levels: Vec<Level>, level_index: usize) -> Self {
+        let level = &levels[level_index];
+        let cell_size = config.cell_size;
+        let max_snake_length = (level.width * level.height) as usize;
+
+        let initial_seed = seed.unwrap_or_else(|| fastrand::u64(..));
+        let difficulty = Difficulty::Normal;
+        let rules = difficulty.rules(&config, difficulty.wall_mode());
+        let simulation = Simulation::new(level, rules, initial_seed);
+        let recording = Replay::new(initial_seed, level, rules, difficulty, None);
+        let run_level = level.name.clone();
+        let (controls, message) = match Controls::load() {
+            Ok(controls) => (controls, None),
+            Err(err) => (Controls::default(), Some(format!("Boshqaruv sozlamalari xatosi: {}", err))),
+        };
 
         let mut game = Game {
-            snake,
-            snake_positions,
-            food: Position { x: 0, y: 0 },
-            direction: Direction::Right,
-            grid_width,
-            grid_height,
+            config,
+            simulation,
+            snake_positions: vec![Vec2Pool::new(max_snake_length)],
             cell_size,
-            score: 0,
             state: GameState::Menu,
-            last_move_time: 0.0,
-            move_interval: 0.12,
-            high_score: 0,
+            accumulator: 0.0,
+            previous_bodies: Vec::new(),
+            high_scores: HashMap::new(),
+            leaderboard: Leaderboard::load(),
+            leaderboard_mode: difficulty.wall_mode(),
+            leaderboard_difficulty: difficulty,
+            leaderboard_level: level_index,
+            name_input: String::new(),
+            name_entry_return: GameState::GameOver,
+            run_time: 0.0,
+            wall_mode: difficulty.wall_mode(),
+            difficulty,
+            run_difficulty: difficulty,
+            run_level,
+            levels,
+            level_index,
+            campaign_progress: CampaignProgress::load(),
+            selected_stage: 0,
+            current_stage: None,
+            versus: false,
+            versus_wins: [0; VERSUS_PLAYERS],
+            round_winner: None,
+            opponents: 0,
+            ai_strategy: AiStrategy::Bfs,
+            ai_level: 3,
+            ai_controllers: Vec::new(),
+            opponent_row: 0,
+            autopilot: None,
+            autopilot_used: false,
+            practice: false,
+            rewind: None,
+            has_save: SaveGame::exists(),
+            controls,
+            controls_row: 0,
+            controls_slot: 0,
+            rebinding: false,
+            gamepads: Gamepads::new(),
+            pointer: Pointer::new(),
+            menu_idle: 0.0,
+            demo: None,
             animation_progress: 0.0,
+            seed_input: seed.map(|seed| seed.to_string()).unwrap_or_default(),
+            message,
 
-            spatial_hash: SpatialHash::new(1),
             texture_cache: TextureCache::new(),
-            input_buffer: InputBuffer::new(3),
+            input_buffers: Vec::new(),
+            tick: 0,
+            recording,
+            last_replay: None,
+            replay_player: None,
             _max_snake_length: max_snake_length,
             _draw_params_cache: Vec::with_capacity(max_snake_length),
         };
 
-        game.spawn_food();
+        game.sync_snake_positions();
         game
     }
 
+    pub fn window_conf(config: &GameConfig) -> Conf {
+        let width = config.grid_width as f32 * config.cell_size + BOARD_MARGIN_X;
+        let height = config.grid_height as f32 * config.cell_size + BOARD_MARGIN_Y;
+        Conf {
+            window_title: "🐍 Ilon".to_string(),
+            window_width: width.clamp(MIN_WINDOW.0, MAX_WINDOW.0) as i32,
+            window_height: height.clamp(MIN_WINDOW.1, MAX_WINDOW.1) as i32,
+            ..Default::default()
+        }
+    }
+
     pub async fn initialize(&mut self) {
         self.texture_cache.load_textures().await;
     }
 
     fn reset(&mut self) {
-        if self.score > self.high_score {
-            self.high_score = self.score;
-        }
+        let difficulty = if self.current_stage.is_some() { Difficulty::Normal } else { self.difficulty };
+        let (level, wall_mode) = match self.current_stage {
+            Some(stage) => {
+                let stage = &STAGES[stage];
+                let level = self.levels
+                    .iter()
+                    .find(|level| level.name == stage.level)
+                    .expect("campaign stage uses a bundled level");
+                (level, stage.wall_mode)
+            }
+            None => (&self.levels[self.level_index], self.wall_mode),
+        };
 
-        self.snake.clear();
-        self.snake.push(Position {
-            x: self.grid_width / 2,
-            y: self.grid_height / 2
-        });
+        let seed = self.seed_input.parse().unwrap_or_else(|_| fastrand::u64(..));
+        self.replay_player = None;
+        self.simulation.load_level(level);
+        self.run_level = level.name.clone();
+        let rules = difficulty.rules(&self.config, wall_mode);
+        self.simulation.set_rules(rules);
+        self.simulation.set_snake_count(self.player_count() + self.opponent_count());
+        self.simulation.reset(seed);
+        let Some(opponents) = self.simulation.snakes().len().checked_sub(self.player_count()) else {
+            self.message = Some("Xaritada barcha o'yinchilar uchun joy yo'q".to_string());
+            self.state = GameState::Menu;
+            return;
+        };
+        self.ai_controllers = (0..opponents.min(self.opponent_count()))
+            .map(|i| AiController::new(self.ai_strategy, self.ai_level, seed.wrapping_add(i as u64 + 1)))
+            .collect();
 
-        self.snake_positions.reset();
-        self.snake_positions.get(
-            (self.grid_width / 2) as f32,
-            (self.grid_height / 2) as f32
-        );
+        self.recording = Replay::new(seed, level, rules, difficulty, self.current_stage);
+        self.set_run_difficulty(difficulty);
+        self.start_run();
+        self.state = GameState::Playing;
+    }
 
-        self.direction = Direction::Right;
-        self.score = 0;
-        self.move_interval = 0.12;
+    fn start_run(&mut self) {
+        self.tick = 0;
+        self.run_time = 0.0;
+        self.accumulator = 0.0;
+        self.remember_bodies();
         self.animation_progress = 0.0;
-        self.input_buffer.clear();
-        self.spawn_food();
-        self.state = GameState::Playing;
+        self.sync_snake_positions();
+        let buffer_size = self.run_difficulty.buffer_size();
+        let max_age = self.config.input_max_age;
+        let humans = self.simulation.snakes().len() - self.ai_controllers.len();
+        self.input_buffers.resize_with(humans, || InputBuffer::new(buffer_size, max_age));
+        for buffer in &mut self.input_buffers {
+            buffer.clear();
+        }
+        self.round_winner = None;
+        self.message = None;
+
+        if self.autopilot.is_some() {
+            self.autopilot = Some(Autopilot::new(&self.simulation));
+        }
+        self.autopilot_used = self.autopilot.is_some();
+        self.rewind = (self.practice && self.replay_player.is_none()).then(|| RewindBuffer::new(REWIND_CAPACITY));
     }
 
-    fn spawn_food(&mut self) {
-        self.update_spatial_hash();
+    fn player_count(&self) -> usize {
+        if self.versus && self.current_stage.is_none() && self.replay_player.is_none() {
+            VERSUS_PLAYERS
+        } else {
+            1
+        }
+    }
 
-        loop {
-            let x = rand::gen_range(0, self.grid_width);
-            let y = rand::gen_range(0, self.grid_height);
-            let pos = Position { x, y };
+    fn opponent_count(&self) -> usize {
+        if self.current_stage.is_none() && self.replay_player.is_none() {
+            self.opponents
+        } else {
+            0
+        }
+    }
 
-            if !self.spatial_hash.contains(pos) {
-                self.food = pos;
-                break;
+    fn end_run(&mut self, state: GameState) {
+        self.state = state;
+
+        if self.replay_player.is_some() {
+            return;
+        }
+
+        if self.input_buffers.len() > 1 {
+            self.last_replay = None;
+            self.end_round();
+            return;
+        }
+        if !self.ai_controllers.is_empty() {
+            self.last_replay = None;
+            return;
+        }
+
+        let score = self.simulation.player().score;
+        let ranked = !self.autopilot_used && self.rewind.is_none();
+        let high_score = self.high_scores.entry(self.run_mode_key()).or_insert(0);
+        if ranked && score > *high_score {
+            *high_score = score;
+        }
+
+        if ranked
+            && self.state == GameState::StageComplete
+            && let Some(stage) = self.current_stage
+            && self.campaign_progress.unlock(stage + 1)
+            && let Err(err) = self.campaign_progress.save() {
+            self.message = Some(format!("Yutuqni saqlab bo'lmadi: {}", err));
+        }
+
+        self.recording.end_tick = self.tick;
+        let replay = self.recording.clone();
+        if let Err(err) = replay.save(&storage::replays_dir().join("last.ilrp")) {
+            self.message = Some(format!("Takrorni saqlab bo'lmadi: {}", err));
+        }
+        self.last_replay = Some(replay);
+
+        if ranked && self.current_stage.is_none() && self.leaderboard.qualifies(&self.run_mode_key(), score) {
+            while get_char_pressed().is_some() {}
+            self.name_input.clear();
+            self.name_entry_return = self.state;
+            self.state = GameState::NameEntry;
+        }
+    }
+
+    fn end_round(&mut self) {
+        let snakes = self.simulation.snakes();
+        let alive: Vec<usize> = (0..VERSUS_PLAYERS).filter(|&index| snakes[index].alive).collect();
+        self.round_winner = match alive[..] {
+            [winner] => Some(winner),
+            [first, second] if snakes[first].score != snakes[second].score => {
+                Some(if snakes[first].score > snakes[second].score { first } else { second })
             }
+            _ => None,
+        };
+
+        if let Some(winner) = self.round_winner {
+            self.versus_wins[winner] += 1;
+        }
+        self.state = GameState::RoundOver;
+    }
+
+    fn submit_name(&mut self) {
+        let name = if self.name_input.is_empty() { "???".to_string() } else { self.name_input.clone() };
+        let entry = LeaderboardEntry {
+            name,
+            score: self.simulation.player().score,
+            length: self.simulation.player().body.len(),
+            duration: self.run_time,
+            date: leaderboard::unix_now(),
+            seed: self.simulation.seed(),
+        };
+
+        self.leaderboard.insert(&self.run_mode_key(), entry);
+        if let Err(err) = self.leaderboard.save() {
+            self.message = Some(format!("Rekordlarni saqlab bo'lmadi: {}", err));
         }
+        self.state = self.name_entry_return;
     }
 
-    fn update_spatial_hash(&mut self) {
-        self.spatial_hash.clear();
-        for &pos in &self.snake {
-            self.spatial_hash.insert(pos);
+    fn handle_name_input(&mut self) {
+        while let Some(c) = get_char_pressed() {
+            if c.is_ascii_alphanumeric() && self.name_input.len() < NAME_LENGTH {
+                self.name_input.push(c.to_ascii_uppercase());
+            }
+        }
+
+        if is_key_pressed(KeyCode::Backspace) {
+            self.name_input.pop();
+        }
+    }
+
+    fn watch_replay(&mut self, replay: Replay) {
+        let level = match self.levels.iter().find(|level| level.name == replay.level) {
+            Some(level) if level.width == replay.grid_width && level.height == replay.grid_height => level.clone(),
+            _ if replay.level == DEFAULT_LEVEL => Level::open(replay.grid_width, replay.grid_height),
+            Some(_) => {
+                self.message = Some(format!(
+                    "Takror {}x{} o'lchamli xarita uchun yozilgan",
+                    replay.grid_width, replay.grid_height
+                ));
+                return;
+            }
+            None => {
+                self.message = Some(format!("Takror xaritasi topilmadi: {}", replay.level));
+                return;
+            }
+        };
+
+        self.simulation.load_level(&level);
+        self.run_level = level.name;
+        let mut rules = replay.difficulty.rules(&self.config, replay.wall_mode);
+        rules.food_chances = replay.food_chances;
+        rules.powerup_chance = replay.powerup_chance;
+        self.simulation.set_rules(rules);
+        self.set_run_difficulty(replay.difficulty);
+        self.current_stage = replay.stage;
+        self.simulation.set_snake_count(1);
+        self.simulation.reset(replay.seed);
+        self.ai_controllers.clear();
+        self.replay_player = Some(ReplayPlayer::new(replay));
+        self.start_run();
+
+        self.state = GameState::Replay;
+    }
+
+    fn save_and_quit(&mut self) {
+        if self.input_buffers.len() > 1 || !self.ai_controllers.is_empty() {
+            self.message = Some("Bu rejimda o'yinni saqlab bo'lmaydi".to_string());
+            return;
+        }
+
+        let save = SaveGame {
+            simulation: self.simulation.clone(),
+            difficulty: self.run_difficulty,
+            stage: self.current_stage,
+            autopilot: self.autopilot.is_some(),
+            autopilot_used: self.autopilot_used,
+            practice: self.rewind.is_some(),
+            tick: self.tick,
+            run_time: self.run_time,
+            buffered: self.input_buffers[0].directions().collect(),
+            recording: self.recording.clone(),
+        };
+
+        match save.save() {
+            Ok(()) => {
+                self.has_save = true;
+                self.message = Some("O'yin saqlandi".to_string());
+                self.state = GameState::Menu;
+            }
+            Err(err) => self.message = Some(format!("O'yinni saqlab bo'lmadi: {}", err)),
+        }
+    }
+
+    fn continue_saved(&mut self) {
+        let save = match SaveGame::load() {
+            Ok(save) => save,
+            Err(err) => {
+                self.message = Some(format!("Saqlangan o'yinni yuklab bo'lmadi: {}", err));
+                return;
+            }
+        };
+        if let Err(err) = SaveGame::delete() {
+            self.message = Some(format!("Saqlangan o'yinni o'chirib bo'lmadi: {}", err));
+        }
+        self.has_save = false;
+
+        self.simulation = save.simulation;
+        self.current_stage = save.stage.filter(|&stage| stage < STAGES.len());
+        self.set_run_difficulty(save.difficulty);
+        self.run_level = save.recording.level.clone();
+        self.recording = save.recording;
+        self.replay_player = None;
+        self.ai_controllers.clear();
+        self.autopilot = save.autopilot.then(|| Autopilot::new(&self.simulation));
+        self.start_run();
+
+        self.autopilot_used = save.autopilot_used;
+        self.rewind = save.practice.then(|| RewindBuffer::new(REWIND_CAPACITY));
+        self.tick = save.tick;
+        self.run_time = save.run_time;
+        let heading = self.simulation.player().direction;
+        for direction in save.buffered {
+            self.input_buffers[0].add_command(direction, heading, self.run_time);
+        }
+        self.state = GameState::Paused;
+    }
+
+    fn load_last_replay(&mut self) {
+        match Replay::load(&storage::replays_dir().join("last.ilrp")) {
+            Ok(replay) => {
+                self.last_replay = Some(replay.clone());
+                self.watch_replay(replay);
+            }
+            Err(err) => self.message = Some(format!("Takrorni yuklab bo'lmadi: {}", err)),
+        }
+    }
+
+    fn set_run_difficulty(&mut self, difficulty: Difficulty) {
+        if self.run_difficulty != difficulty {
+            self.input_buffers.clear();
+        }
+        self.run_difficulty = difficulty;
+    }
+
+    fn mode_key(difficulty: Difficulty, wall_mode: WallMode, level: &str, width: i32, height: i32) -> String {
+        format!("{}-{}-{}-{}x{}", difficulty.id(), wall_mode.id(), level.replace('\t', " "), width, height)
+    }
+
+    fn run_mode_key(&self) -> String {
+        let (width, height) = (self.simulation.grid_width(), self.simulation.grid_height());
+        Self::mode_key(self.run_difficulty, self.simulation.wall_mode(), &self.run_level, width, height)
+    }
+
+    fn level_mode_key(&self, difficulty: Difficulty, wall_mode: WallMode, level_index: usize) -> String {
+        let level = &self.levels[level_index];
+        Self::mode_key(difficulty, wall_mode, &level.name, level.width, level.height)
+    }
+
+    fn high_score(&self, key: &str) -> i32 {
+        let session_best = self.high_scores.get(key).copied().unwrap_or(0);
+        session_best.max(self.leaderboard.best(key))
+    }
+
+    fn remember_bodies(&mut self) {
+        let snakes = self.simulation.snakes();
+        self.previous_bodies.resize_with(snakes.len(), Vec::new);
+        for (previous, snake) in self.previous_bodies.iter_mut().zip(snakes) {
+            previous.clone_from(&snake.body);
+        }
+    }
+
+    fn sync_snake_positions(&mut self) {
+        let snakes = self.simulation.snakes();
+        let progress = self.animation_progress;
+        self.snake_positions.resize_with(snakes.len(), || Vec2Pool::new(snakes[0].body.capacity()));
+
+        for (index, (pool, snake)) in self.snake_positions.iter_mut().zip(snakes).enumerate() {
+            let previous = self.previous_bodies.get(index).map_or(&snake.body[..], Vec::as_slice);
+            pool.reset();
+            for (i, &pos) in snake.body.iter().enumerate() {
+                let from_head = snake.body.len() - i;
+                let from = previous.len().checked_sub(from_head).map_or(pos, |j| previous[j]);
+                let step = |d: i32| (if d.abs() > 1 { -d.signum() } else { d }) as f32;
+                pool.get(
+                    from.x as f32 + step(pos.x - from.x) * progress,
+                    from.y as f32 + step(pos.y - from.y) * progress,
+                );
+            }
         }
     }
 
     pub fn handle_input(&mut self) {
-        if self.state == GameState::Playing {
-            let new_direction = if is_key_pressed(KeyCode::Up) || is_key_pressed(KeyCode::W) {
-                Some(Direction::Up)
-            } else if is_key_pressed(KeyCode::Down) || is_key_pressed(KeyCode::S) {
-                Some(Direction::Down)
-            } else if is_key_pressed(KeyCode::Left) || is_key_pressed(KeyCode::A) {
-                Some(Direction::Left)
-            } else if is_key_pressed(KeyCode::Right) || is_key_pressed(KeyCode::D) {
-                Some(Direction::Right)
-            } else {
-                None
-            };
+        self.gamepads.update();
+        let gesture = self.pointer.update(self.config.min_swipe_distance);
+        let tap = match gesture {
+            Some(Gesture::Tap(position)) => Some(position),
+            _ => None,
+        };
+        if self.state != GameState::Menu
+            || get_last_key_pressed().is_some()
+            || self.gamepads.any_input()
+            || gesture.is_some() {
+            self.menu_idle = 0.0;
+            self.demo = None;
+        }
 
-            if let Some(dir) = new_direction {
-                let valid = match (&self.direction, &dir) {
-                    (Direction::Up, Direction::Down) |
-                    (Direction::Down, Direction::Up) |
-                    (Direction::Left, Direction::Right) |
-                    (Direction::Right, Direction::Left) => false,
-                    _ => true,
-                };
+        if self.state == GameState::Playing {
+            let primary = self.controls.pressed_turn(false).or(self.gamepads.turn(0));
+            let alt = self.controls.pressed_turn(true).or(self.gamepads.turn(1));
 
-                if valid {
-                    if self.input_buffer.commands.is_empty() {
-                        self.direction = dir;
-                        self.animation_progress = 0.0;
-                        self.last_move_time = get_time() - self.move_interval * 0.8;
-                    } else {
-                        self.input_buffer.add_command(dir);
-                    }
+            if self.input_buffers.len() > 1 {
+                self.steer(1, alt);
+                if self.autopilot.is_none() {
+                    self.steer(0, primary);
                 }
+            } else if self.autopilot.is_none() {
+                self.steer(0, alt.or(primary).or(self.gamepads.any_turn()));
+            }
+
+            if self.autopilot.is_none() {
+                let touch = match gesture {
+                    Some(Gesture::Swipe(direction)) => Some(direction),
+                    Some(Gesture::Tap(position)) => self.turn_towards(position),
+                    None => None,
+                };
+                self.queue_direction(0, touch);
             }
         }
 
         match self.state {
             GameState::Menu => {
-                if is_key_pressed(KeyCode::Space) {
+                self.handle_seed_input();
+                let clicked = tap.and_then(|position| ui::clicked(&self.menu_buttons(), position));
+                if self.has_save && (clicked == Some(UiAction::Continue) || self.action_pressed(Action::Continue)) {
+                    self.continue_saved();
+                } else if clicked == Some(UiAction::Start) || self.action_pressed(Action::Confirm) {
+                    self.current_stage = None;
                     self.reset();
+                } else if clicked == Some(UiAction::Leaderboard) || self.action_pressed(Action::OpenLeaderboard) {
+                    self.leaderboard_mode = self.wall_mode;
+                    self.leaderboard_difficulty = self.difficulty;
+                    self.leaderboard_level = self.level_index;
+                    self.state = GameState::Leaderboard;
+                } else if clicked == Some(UiAction::Campaign) || self.action_pressed(Action::OpenCampaign) {
+                    self.selected_stage = self.campaign_progress.unlocked;
+                    self.state = GameState::LevelSelect;
+                } else if self.action_pressed(Action::LastReplay) {
+                    self.load_last_replay();
+                } else if self.action_pressed(Action::TogglePractice) {
+                    self.practice = !self.practice;
+                } else if self.action_pressed(Action::ToggleWallMode) {
+                    self.wall_mode = self.wall_mode.toggled();
+                } else if self.action_pressed(Action::CycleDifficulty) {
+                    self.difficulty = self.difficulty.next();
+                    self.wall_mode = self.difficulty.wall_mode();
+                } else if self.action_pressed(Action::NextLevel) {
+                    self.level_index = (self.level_index + 1) % self.levels.len();
+                } else if self.action_pressed(Action::ToggleVersus) {
+                    self.versus = !self.versus;
+                    self.versus_wins = [0; VERSUS_PLAYERS];
+                } else if self.action_pressed(Action::OpenOpponents) {
+                    self.opponent_row = 0;
+                    self.state = GameState::Opponents;
+                } else if clicked == Some(UiAction::Controls) || self.action_pressed(Action::OpenControls) {
+                    self.controls_row = 0;
+                    self.controls_slot = 0;
+                    self.rebinding = false;
+                    self.message = None;
+                    self.state = GameState::Controls;
                 }
             }
             GameState::Playing => {
-                if is_key_pressed(KeyCode::Escape) {
+                if self.action_pressed(Action::Pause) {
                     self.state = GameState::Paused;
+                } else if self.action_pressed(Action::Autopilot) {
+                    self.toggle_autopilot();
+                } else if self.action_pressed(Action::Rewind) {
+                    self.start_rewind();
+                }
+            }
+            GameState::Rewinding => {
+                if !self.action_down(Action::Rewind) {
+                    self.stop_rewind();
                 }
             }
             GameState::Paused => {
-                if is_key_pressed(KeyCode::Escape) {
+                if self.action_pressed(Action::Pause) {
+                    self.message = None;
                     self.state = GameState::Playing;
+                } else if self.action_pressed(Action::SaveAndQuit) {
+                    self.save_and_quit();
                 }
             }
-            GameState::GameOver => {
-                if is_key_pressed(KeyCode::Space) {
+            GameState::GameOver | GameState::Victory => {
+                let clicked = tap.and_then(|position| ui::clicked(&self.game_over_buttons(), position));
+                if clicked == Some(UiAction::Retry) || self.action_pressed(Action::Confirm) {
+                    self.reset();
+                } else if self.action_pressed(Action::Rewind) && self.state == GameState::GameOver {
+                    self.start_rewind();
+                } else if clicked == Some(UiAction::WatchReplay) || self.action_pressed(Action::WatchReplay) {
+                    if let Some(replay) = self.last_replay.clone() {
+                        self.watch_replay(replay);
+                    }
+                } else if clicked == Some(UiAction::Exit) || self.action_pressed(Action::Back) {
+                    self.state = self.exit_state();
+                }
+            }
+            GameState::Opponents => self.handle_opponents_input(),
+            GameState::Controls => self.handle_controls_input(),
+            GameState::RoundOver => {
+                if self.action_pressed(Action::Confirm) {
                     self.reset();
-                } else if is_key_pressed(KeyCode::Escape) {
+                } else if self.action_pressed(Action::Back) {
                     self.state = GameState::Menu;
                 }
             }
+            GameState::Replay => {
+                if self.action_pressed(Action::Back) {
+                    self.state = self.exit_state();
+                }
+            }
+            GameState::NameEntry => {
+                self.handle_name_input();
+                if is_key_pressed(KeyCode::Enter) {
+                    self.submit_name();
+                }
+            }
+            GameState::Leaderboard => {
+                let turn = self.any_turn_pressed();
+                if matches!(turn, Some(Direction::Left | Direction::Right)) {
+                    self.leaderboard_mode = self.leaderboard_mode.toggled();
+                } else if turn.is_some() {
+                    self.leaderboard_difficulty = self.leaderboard_difficulty.next();
+                } else if self.action_pressed(Action::NextLevel) {
+                    self.leaderboard_level = (self.leaderboard_level + 1) % self.levels.len();
+                } else if self.action_pressed(Action::Back) || self.action_pressed(Action::Confirm) {
+                    self.state = GameState::Menu;
+                }
+            }
+            GameState::LevelSelect => {
+                let turn = self.any_turn_pressed();
+                if turn == Some(Direction::Up) {
+                    self.selected_stage = self.selected_stage.saturating_sub(1);
+                } else if turn == Some(Direction::Down) {
+                    self.selected_stage = (self.selected_stage + 1).min(self.campaign_progress.unlocked);
+                } else if self.action_pressed(Action::Confirm) {
+                    self.current_stage = Some(self.selected_stage);
+                    self.reset();
+                } else if self.action_pressed(Action::Back) {
+                    self.state = GameState::Menu;
+                }
+            }
+            GameState::StageComplete => {
+                let next_stage = self.current_stage.map_or(0, |stage| stage + 1);
+                let confirm = self.action_pressed(Action::Confirm);
+                if confirm && next_stage < STAGES.len() && self.replay_player.is_none() {
+                    self.current_stage = Some(next_stage);
+                    self.reset();
+                } else if confirm || self.action_pressed(Action::Back) {
+                    self.selected_stage = next_stage.min(self.campaign_progress.unlocked);
+                    self.state = GameState::LevelSelect;
+                }
+            }
         }
     }
 
-    pub fn update(&mut self) {
-        if self.state != GameState::Playing {
-            return;
+    fn toggle_autopilot(&mut self) {
+        if self.autopilot.take().is_none() {
+            self.autopilot = Some(Autopilot::new(&self.simulation));
+            self.autopilot_used = true;
+            self.input_buffers[0].clear();
         }
+    }
 
-        let current_time = get_time();
-        let time_since_last_move = current_time - self.last_move_time;
-
-        self.animation_progress = (time_since_last_move / self.move_interval).min(1.0) as f32;
+    fn start_rewind(&mut self) {
+        if self.rewind.is_some() {
+            self.step_back();
+            self.accumulator = 0.0;
+            self.message = None;
+            self.state = GameState::Rewinding;
+        }
+    }
 
-        if time_since_last_move < self.move_interval {
-            return;
+    fn stop_rewind(&mut self) {
+        for buffer in &mut self.input_buffers {
+            buffer.clear();
         }
+        self.accumulator = 0.0;
+        self.state = if self.simulation.player().alive { GameState::Playing } else { GameState::GameOver };
+    }
 
-        self.last_move_time = current_time;
+    fn step_back(&mut self) {
+        let Some(snapshot) = self.rewind.as_mut().and_then(RewindBuffer::pop) else { return };
 
-        if let Some(buffered_direction) = self.input_buffer.get_next_command() {
-            let valid = match (&self.direction, &buffered_direction) {
-                (Direction::Up, Direction::Down) |
-                (Direction::Down, Direction::Up) |
-                (Direction::Left, Direction::Right) |
-                (Direction::Right, Direction::Left) => false,
-                _ => true,
-            };
+        self.simulation = snapshot.simulation;
+        self.tick = snapshot.tick;
+        self.recording.truncate(snapshot.tick);
+        self.remember_bodies();
+    }
+
+    fn handle_opponents_input(&mut self) {
+        let turn = self.any_turn_pressed();
+        let step = match turn {
+            Some(Direction::Right) => 1,
+            Some(Direction::Left) => -1,
+            _ => 0,
+        };
 
-            if valid {
-                self.direction = buffered_direction;
+        if turn == Some(Direction::Up) {
+            self.opponent_row = self.opponent_row.saturating_sub(1);
+        } else if turn == Some(Direction::Down) {
+            self.opponent_row = (self.opponent_row + 1).min(2);
+        } else if self.action_pressed(Action::Back) || self.action_pressed(Action::Confirm) {
+            self.state = GameState::Menu;
+        } else if step != 0 {
+            match self.opponent_row {
+                0 => self.opponents = self.opponents.saturating_add_signed(step).min(MAX_OPPONENTS),
+                1 => self.ai_strategy = self.ai_strategy.next(),
+                _ => self.ai_level = self.ai_level.saturating_add_signed(step as i32).clamp(1, ai::MAX_LEVEL),
             }
         }
+    }
 
-        self.animation_progress = 0.0;
+    fn projected_heading(&self, player: usize) -> Direction {
+        self.input_buffers[player].projected_heading(self.simulation.snakes()[player].direction)
+    }
 
-        let head = self.snake[self.snake.len() - 1];
-        let new_head = match self.direction {
-            Direction::Up => Position { x: head.x, y: head.y - 1 },
-            Direction::Down => Position { x: head.x, y: head.y + 1 },
-            Direction::Left => Position { x: head.x - 1, y: head.y },
-            Direction::Right => Position { x: head.x + 1, y: head.y },
+    fn steer(&mut self, player: usize, input: Option<Direction>) {
+        let scheme = self.controls.scheme(player);
+        let direction = input.and_then(|input| scheme.resolve(input, self.projected_heading(player)));
+        self.queue_direction(player, direction);
+    }
+
+    fn turn_towards(&self, position: Vec2) -> Option<Direction> {
+        let heading = self.projected_heading(0);
+        let head = self.simulation.player().head();
+        let center = self.board_offset() + (vec2(head.x as f32, head.y as f32) + 0.5) * self.cell_size;
+        let delta = position - center;
+
+        let direction = match heading {
+            Direction::Up | Direction::Down => pointer::direction_towards(vec2(delta.x, 0.0)),
+            Direction::Left | Direction::Right => pointer::direction_towards(vec2(0.0, delta.y)),
         };
+        (delta.length() > self.cell_size / 2.0).then_some(direction)
+    }
 
-        if new_head.x < 0 || new_head.x >= self.grid_width ||
-            new_head.y < 0 || new_head.y >= self.grid_height {
-            self.state = GameState::GameOver;
+    fn menu_buttons(&self) -> Vec<Button<UiAction>> {
+        let mut items = vec![("Boshlash", UiAction::Start)];
+        if self.has_save {
+            items.push(("Davom ettirish", UiAction::Continue));
+        }
+        items.extend([
+            ("Kampaniya", UiAction::Campaign),
+            ("Rekordlar", UiAction::Leaderboard),
+            ("Boshqaruv", UiAction::Controls),
+        ]);
+        ui::button_row(items, 30.0)
+    }
+
+    fn game_over_buttons(&self) -> Vec<Button<UiAction>> {
+        let mut items = vec![("Qayta o'ynash", UiAction::Retry)];
+        if self.last_replay.is_some() {
+            items.push(("Takror", UiAction::WatchReplay));
+        }
+        items.push(("Orqaga", UiAction::Exit));
+        ui::button_row(items, screen_height() / 2.0 + 150.0)
+    }
+
+    fn action_pressed(&self, action: Action) -> bool {
+        self.controls.pressed(action) || self.gamepads.action_pressed(action)
+    }
+
+    fn action_down(&self, action: Action) -> bool {
+        self.controls.down(action) || self.gamepads.action_down(action)
+    }
+
+    fn any_turn_pressed(&self) -> Option<Direction> {
+        self.controls.pressed_any_turn().or(self.gamepads.any_turn())
+    }
+
+    fn handle_controls_input(&mut self) {
+        let action = Action::ALL[self.controls_row];
+
+        if self.rebinding {
+            let Some(key) = get_last_key_pressed() else { return };
+            self.rebinding = false;
+            if !controls::is_bindable(action, key) {
+                self.message = Some(format!("{} tugmasini belgilab bo'lmaydi", controls::key_name(key)));
+                return;
+            }
+            match self.controls.bind(action, self.controls_slot, key) {
+                Ok(()) => self.save_controls(),
+                Err(other) => {
+                    self.message = Some(format!(
+                        "{} allaqachon \"{}\" uchun ishlatilgan",
+                        controls::key_name(key),
+                        other.label()
+                    ));
+                }
+            }
             return;
         }
 
-        if self.spatial_hash.contains(new_head) {
-            self.state = GameState::GameOver;
+        if is_key_pressed(KeyCode::Up) {
+            self.controls_row = self.controls_row.saturating_sub(1);
+        } else if is_key_pressed(KeyCode::Down) {
+            self.controls_row = (self.controls_row + 1).min(Action::ALL.len() - 1);
+        } else if is_key_pressed(KeyCode::Left) {
+            self.controls_slot = self.controls_slot.saturating_sub(1);
+        } else if is_key_pressed(KeyCode::Right) {
+            self.controls_slot = (self.controls_slot + 1).min(KEYS_PER_ACTION - 1);
+        } else if is_key_pressed(KeyCode::Enter) {
+            self.rebinding = true;
+            self.message = None;
+        } else if is_key_pressed(KeyCode::Delete) {
+            if self.controls.unbind(action, self.controls_slot) {
+                self.save_controls();
+            } else {
+                self.message = Some("Har bir amalga kamida bitta tugma kerak".to_string());
+            }
+        } else if let Some(player) = [KeyCode::Key1, KeyCode::Key2].iter().position(|&key| is_key_pressed(key)) {
+            self.controls.toggle_scheme(player);
+            self.save_controls();
+        } else if is_key_pressed(KeyCode::F5) {
+            self.controls = Controls::default();
+            self.save_controls();
+        } else if is_key_pressed(KeyCode::Escape) {
+            self.message = None;
+            self.state = GameState::Menu;
+        }
+    }
+
+    fn save_controls(&mut self) {
+        self.message = match self.controls.save() {
+            Ok(()) => None,
+            Err(err) => Some(format!("Boshqaruv sozlamalarini saqlab bo'lmadi: {}", err)),
+        };
+    }
+
+    fn queue_direction(&mut self, player: usize, direction: Option<Direction>) {
+        if let Some(dir) = direction
+            && let Some(snake) = self.simulation.snakes().get(player)
+            && snake.alive {
+            let buffer = &mut self.input_buffers[player];
+            let was_empty = buffer.is_empty();
+            if buffer.add_command(dir, snake.direction, self.run_time) && was_empty {
+                self.accumulator = self.accumulator.max(self.simulation.move_interval() * 0.8);
+            }
+        }
+    }
+
+    fn exit_state(&self) -> GameState {
+        if self.current_stage.is_some() {
+            GameState::LevelSelect
+        } else {
+            GameState::Menu
+        }
+    }
+
+    fn handle_seed_input(&mut self) {
+        while let Some(c) = get_char_pressed() {
+            if c.is_ascii_digit() && self.seed_input.len() < 19 {
+                self.seed_input.push(c);
+            }
+        }
+
+        if is_key_pressed(KeyCode::Backspace) {
+            self.seed_input.pop();
+        }
+    }
+
+    pub fn update(&mut self) {
+        self.fit_board();
+        if self.state == GameState::Menu {
+            self.update_demo();
+            return;
+        }
+        if self.state == GameState::Rewinding {
+            self.update_rewind();
+            return;
+        }
+        if self.state != GameState::Playing && self.state != GameState::Replay {
             return;
         }
 
-        self.snake.push(new_head);
-        self.snake_positions.get(new_head.x as f32, new_head.y as f32);
+        let frame_time = (get_frame_time() as f64).min(MAX_FRAME_TIME);
+        self.accumulator += frame_time;
+        self.run_time += frame_time;
 
-        if new_head == self.food {
-            self.score += 10;
-            self.spawn_food();
+        let mut finished = false;
+        while self.accumulator >= self.simulation.move_interval() {
+            self.accumulator -= self.simulation.move_interval();
+            self.tick_simulation();
 
-            if self.move_interval > 0.04 {
-                self.move_interval *= 0.97;
+            if self.state != GameState::Playing && self.state != GameState::Replay {
+                self.accumulator = 0.0;
+                finished = true;
+                break;
             }
+        }
+
+        self.animation_progress = if finished {
+            1.0
         } else {
-            self.snake.remove(0);
-            self.snake_positions.reset();
-            for &pos in &self.snake {
-                self.snake_positions.get(pos.x as f32, pos.y as f32);
+            (self.accumulator / self.simulation.move_interval()).min(1.0) as f32
+        };
+        self.sync_snake_positions();
+    }
+
+    fn update_rewind(&mut self) {
+        self.accumulator += (get_frame_time() as f64).min(MAX_FRAME_TIME);
+        while self.accumulator >= REWIND_INTERVAL {
+            self.accumulator -= REWIND_INTERVAL;
+            self.step_back();
+        }
+        self.animation_progress = 1.0;
+        self.sync_snake_positions();
+    }
+
+    fn update_demo(&mut self) {
+        let frame_time = (get_frame_time() as f64).min(MAX_FRAME_TIME);
+
+        if self.demo.is_none() {
+            self.menu_idle += frame_time;
+            if self.menu_idle >= ATTRACT_DELAY {
+                self.start_demo();
+            }
+            return;
+        }
+
+        self.accumulator += frame_time;
+        while self.accumulator >= self.simulation.move_interval() {
+            self.accumulator -= self.simulation.move_interval();
+            self.tick_demo();
+        }
+        self.animation_progress = (self.accumulator / self.simulation.move_interval()).min(1.0) as f32;
+        self.sync_snake_positions();
+    }
+
+    fn start_demo(&mut self) {
+        let level = &self.levels[self.level_index];
+        self.simulation.load_level(level);
+        self.simulation.set_rules(self.difficulty.rules(&self.config, self.wall_mode));
+        self.simulation.set_snake_count(1);
+        self.simulation.reset(fastrand::u64(..));
+
+        self.demo = Some(Autopilot::new(&self.simulation));
+        self.remember_bodies();
+        self.accumulator = 0.0;
+        self.animation_progress = 0.0;
+        self.sync_snake_positions();
+    }
+
+    fn tick_demo(&mut self) {
+        let Some(demo) = &self.demo else { return };
+        let input = demo.choose(&self.simulation, 0);
+
+        self.remember_bodies();
+        let events = self.simulation.step(&[input]);
+
+        if events.contains(&SimEvent::Died(0)) || events.contains(&SimEvent::Won) {
+            self.start_demo();
+        }
+    }
+
+    fn tick_simulation(&mut self) {
+        let inputs: Vec<Option<Direction>> = match &mut self.replay_player {
+            Some(player) => vec![player.input_for(self.tick)],
+            None => {
+                if let Some(autopilot) = &self.autopilot
+                    && let Some(direction) = autopilot.choose(&self.simulation, 0)
+                    && direction != self.simulation.player().direction {
+                    self.input_buffers[0].add_command(direction, self.simulation.player().direction, self.run_time);
+                }
+
+                let mut inputs: Vec<_> = self
+                    .input_buffers
+                    .iter_mut()
+                    .zip(self.simulation.snakes())
+                    .map(|(buffer, snake)| buffer.get_next_command(snake.direction, self.run_time))
+                    .collect();
+                if let [Some(direction)] = inputs[..] {
+                    self.recording.record(self.tick, direction);
+                }
+                let humans = inputs.len();
+                for (i, controller) in self.ai_controllers.iter_mut().enumerate() {
+                    inputs.push(controller.choose(&self.simulation, humans + i));
+                }
+                inputs
+            }
+        };
+
+        if let Some(rewind) = &mut self.rewind {
+            rewind.push(&self.simulation, self.tick);
+        }
+        self.remember_bodies();
+        let events = self.simulation.step(&inputs);
+        self.tick += 1;
+
+        if self.input_buffers.len() > 1 {
+            let survivors = self.simulation.snakes()[..VERSUS_PLAYERS].iter().filter(|snake| snake.alive).count();
+            if survivors <= 1 || events.contains(&SimEvent::Won) {
+                self.end_run(GameState::RoundOver);
             }
+            return;
         }
 
-        self.update_spatial_hash();
+        let stage_cleared = self.current_stage.is_some_and(|stage| STAGES[stage].goal.is_met(&self.simulation));
+        let replay_finished = self.replay_player.as_ref().is_some_and(|player| player.finished(self.tick));
+
+        if events.contains(&SimEvent::Won) && self.current_stage.is_none() {
+            self.end_run(GameState::Victory);
+        } else if events.contains(&SimEvent::Died(0)) {
+            self.end_run(GameState::GameOver);
+        } else if stage_cleared || events.contains(&SimEvent::Won) {
+            self.end_run(GameState::StageComplete);
+        } else if replay_finished {
+            self.end_run(GameState::GameOver);
+        }
     }
 
     pub fn draw(&self) {
@@ -268,6 +1079,34 @@ impl Game {
                 self.draw_game();
                 self.draw_game_over();
             }
+            GameState::Victory => {
+                self.draw_game();
+                self.draw_victory();
+            }
+            GameState::Replay => {
+                self.draw_game();
+                self.draw_replay_overlay();
+            }
+            GameState::Rewinding => {
+                self.draw_game();
+                self.draw_rewind_overlay();
+            }
+            GameState::NameEntry => {
+                self.draw_game();
+                self.draw_name_entry();
+            }
+            GameState::Leaderboard => self.draw_leaderboard(),
+            GameState::LevelSelect => self.draw_level_select(),
+            GameState::StageComplete => {
+                self.draw_game();
+                self.draw_stage_complete();
+            }
+            GameState::RoundOver => {
+                self.draw_game();
+                self.draw_round_over();
+            }
+            GameState::Opponents => self.draw_opponents(),
+            GameState::Controls => self.draw_controls(),
         }
     }
 
@@ -275,6 +1114,11 @@ impl Game {
         let screen_width = screen_width();
         let screen_height = screen_height();
 
+        if self.demo.is_some() {
+            self.draw_board();
+            draw_rectangle(0.0, 0.0, screen_width, screen_height, Color::from_rgba(20, 25, 40, 190));
+        }
+
         let title = "🐍 Ilon O'yini 🐍";
         let title_size = 60.0;
         let title_dims = measure_text(title, None, title_size as u16, 1.0);
@@ -286,19 +1130,76 @@ impl Game {
             GREEN,
         );
 
-        let high_score_text = format!("Rekord: {}", self.high_score);
-        let instructions = vec![
-            "Space - O'yinni boshlash",
-            "WASD yoki strelkalar - boshqarish",
-            "ESC - pauza",
-            "",
+        let menu_key = self.level_mode_key(self.difficulty, self.wall_mode, self.level_index);
+        let high_score_text = format!("Rekord: {}", self.high_score(&menu_key));
+        let mode_text = format!(
+            "{} - qiyinlik: {} | {} - rejim: {}",
+            self.controls.hint(Action::CycleDifficulty),
+            self.difficulty.label(),
+            self.controls.hint(Action::ToggleWallMode),
+            self.wall_mode.label()
+        );
+        let level_text = format!(
+            "{} - xarita: {}",
+            self.controls.hint(Action::NextLevel),
+            self.levels[self.level_index].name
+        );
+        let versus_text = format!(
+            "{} - ikki o'yinchi: {} | {} - raqiblar: {}",
+            self.controls.hint(Action::ToggleVersus),
+            if self.versus { "ha" } else { "yo'q" },
+            self.controls.hint(Action::OpenOpponents),
+            self.opponents
+        );
+        let practice_text = format!(
+            "{} - oxirgi takrorni ko'rish | {} - mashq rejimi (orqaga qaytarish): {}",
+            self.controls.hint(Action::LastReplay),
+            self.controls.hint(Action::TogglePractice),
+            if self.practice { "ha" } else { "yo'q" }
+        );
+        let screens_text = format!(
+            "{} - kampaniya | {} - rekordlar jadvali | {} - boshqaruv",
+            self.controls.hint(Action::OpenCampaign),
+            self.controls.hint(Action::OpenLeaderboard),
+            self.controls.hint(Action::OpenControls)
+        );
+        let seed_text = if self.seed_input.is_empty() {
+            "Seed: tasodifiy (raqamlar - kiritish)".to_string()
+        } else {
+            format!("Seed: {} (Backspace - o'chirish)", self.seed_input)
+        };
+        let start_text = format!(
+            "{} - O'yinni boshlash | {} - pauza | {} - avtopilot",
+            self.controls.hint(Action::Confirm),
+            self.controls.hint(Action::Pause),
+            self.controls.hint(Action::Autopilot)
+        );
+        let turn_text = format!(
+            "{} yoki {} - boshqarish",
+            self.controls.turn_hint(false),
+            self.controls.turn_hint(true)
+        );
+        let continue_text = if self.has_save {
+            format!("{} - saqlangan o'yinni davom ettirish", self.controls.hint(Action::Continue))
+        } else {
+            String::new()
+        };
+        let instructions = [
+            start_text.as_str(),
+            &turn_text,
+            &screens_text,
+            &practice_text,
+            &mode_text,
+            &level_text,
+            &versus_text,
+            &seed_text,
+            &continue_text,
             &high_score_text,
-            "",
             "⚡ OPTIMAL VERSIYA ⚡",
         ];
 
         for (i, instruction) in instructions.iter().enumerate() {
-            let y = screen_height / 2.0 - 20.0 + i as f32 * 40.0;
+            let y = screen_height / 2.0 - 60.0 + i as f32 * 28.0;
             let dims = measure_text(instruction, None, 24, 1.0);
             draw_text(
                 instruction,
@@ -308,137 +1209,323 @@ impl Game {
                 if instruction.contains("OPTIMAL") { YELLOW } else { WHITE },
             );
         }
+
+        self.draw_message(screen_height - 30.0);
+        ui::draw_buttons(&self.menu_buttons());
+    }
+
+    fn draw_message(&self, y: f32) {
+        if let Some(message) = &self.message {
+            let dims = measure_text(message, None, 20, 1.0);
+            draw_text(message, (screen_width() - dims.width) / 2.0, y, 20.0, ORANGE);
+        }
     }
 
     fn draw_game(&self) {
-        let offset_x = (screen_width() - self.grid_width as f32 * self.cell_size) / 2.0;
-        let offset_y = (screen_height() - self.grid_height as f32 * self.cell_size) / 2.0 + 30.0;
+        self.draw_board();
+        self.draw_ui();
+    }
+
+    fn fit_board(&mut self) {
+        let grid_width = self.simulation.grid_width() as f32;
+        let grid_height = self.simulation.grid_height() as f32;
+        let fit_x = (screen_width() - BOARD_MARGIN_X) / grid_width;
+        let fit_y = (screen_height() - BOARD_MARGIN_Y) / grid_height;
+        self.cell_size = self.config.cell_size.min(fit_x).min(fit_y).max(1.0);
+    }
+
+    fn cell_padding(&self) -> f32 {
+        (self.cell_size / 12.0).min(2.0)
+    }
+
+    fn board_offset(&self) -> Vec2 {
+        let grid_width = self.simulation.grid_width() as f32;
+        let grid_height = self.simulation.grid_height() as f32;
+        vec2(
+            (screen_width() - grid_width * self.cell_size) / 2.0,
+            (screen_height() - grid_height * self.cell_size) / 2.0 + 30.0,
+        )
+    }
+
+    fn draw_board(&self) {
+        let Vec2 { x: offset_x, y: offset_y } = self.board_offset();
 
         self.draw_grid(offset_x, offset_y);
+        self.draw_walls(offset_x, offset_y);
         self.draw_snake_batched(offset_x, offset_y);
         self.draw_food(offset_x, offset_y);
-        self.draw_ui();
+        self.draw_powerups(offset_x, offset_y);
     }
 
     fn draw_grid(&self, offset_x: f32, offset_y: f32) {
         let grid_color = Color::from_rgba(40, 45, 60, 255);
+        let grid_width = self.simulation.grid_width();
+        let grid_height = self.simulation.grid_height();
 
-        for x in 0..=self.grid_width {
+        for x in 0..=grid_width {
             let x_pos = offset_x + x as f32 * self.cell_size;
             draw_line(
                 x_pos, offset_y,
-                x_pos, offset_y + self.grid_height as f32 * self.cell_size,
+                x_pos, offset_y + grid_height as f32 * self.cell_size,
                 1.0, grid_color,
             );
         }
 
-        for y in 0..=self.grid_height {
+        for y in 0..=grid_height {
             let y_pos = offset_y + y as f32 * self.cell_size;
             draw_line(
                 offset_x, y_pos,
-                offset_x + self.grid_width as f32 * self.cell_size, y_pos,
+                offset_x + grid_width as f32 * self.cell_size, y_pos,
                 1.0, grid_color,
             );
         }
     }
 
-    fn draw_snake_batched(&self, offset_x: f32, offset_y: f32) {
-        let positions = self.snake_positions.get_slice();
+    fn draw_walls(&self, offset_x: f32, offset_y: f32) {
+        let wall_color = Color::from_rgba(90, 100, 130, 255);
 
-        for (i, (_pos, smooth_pos)) in self.snake.iter().zip(positions.iter()).enumerate() {
-            let mut current_pos = *smooth_pos;
+        for wall in self.simulation.walls() {
+            draw_rectangle(
+                offset_x + wall.x as f32 * self.cell_size,
+                offset_y + wall.y as f32 * self.cell_size,
+                self.cell_size,
+                self.cell_size,
+                wall_color,
+            );
+        }
+    }
 
-            if i == self.snake.len() - 1 && self.animation_progress > 0.0 {
-                let target_pos = match self.direction {
-                    Direction::Up => Vec2::new(current_pos.x, current_pos.y - self.animation_progress),
-                    Direction::Down => Vec2::new(current_pos.x, current_pos.y + self.animation_progress),
-                    Direction::Left => Vec2::new(current_pos.x - self.animation_progress, current_pos.y),
-                    Direction::Right => Vec2::new(current_pos.x + self.animation_progress, current_pos.y),
-                };
-                current_pos = target_pos;
-            }
-
-            let x = offset_x + current_pos.x * self.cell_size + 2.0;
-            let y = offset_y + current_pos.y * self.cell_size + 2.0;
-            let size = self.cell_size - 4.0;
-
-            if i == self.snake.len() - 1 {
-                if let Some(texture) = &self.texture_cache.snake_head {
-                    let rotation = match self.direction {
-                        Direction::Right => 0.0,
-                        Direction::Down => std::f32::consts::PI / 2.0,
-                        Direction::Left => std::f32::consts::PI,
-                        Direction::Up => -std::f32::consts::PI / 2.0,
-                    };
-
-                    draw_texture_ex(
-                        texture,
-                        x,
-                        y,
-                        WHITE,
-                        DrawTextureParams {
-                            dest_size: Some(Vec2::new(size, size)),
-                            rotation,
-                            ..Default::default()
-                        },
-                    );
-                } else {
-                    draw_rectangle(x, y, size, size, Color::from_rgba(50, 255, 50, 255));
-                }
-            } else {
-                if let Some(texture) = &self.texture_cache.snake_body {
-                    let alpha = (200 - (i * 15).min(150)) as u8;
-                    draw_texture_ex(
-                        texture,
-                        x,
-                        y,
-                        Color::from_rgba(255, 255, 255, alpha),
-                        DrawTextureParams {
-                            dest_size: Some(Vec2::new(size, size)),
-                            ..Default::default()
-                        },
-                    );
-                } else {
-                    let alpha = (200 - (i * 15).min(150)) as u8;
-                    draw_rectangle(x, y, size, size, Color::from_rgba(0, 200, 0, alpha));
+    fn draw_snake_batched(&self, offset_x: f32, offset_y: f32) {
+        let padding = self.cell_padding();
+        let size = self.cell_size - padding * 2.0;
+        let offset = vec2(offset_x, offset_y);
+        let center = |pos: Vec2| offset + (pos + 0.5) * self.cell_size;
+        let background = Color::from_rgba(20, 25, 40, 255);
+
+        for (index, (snake, pool)) in self.simulation.snakes().iter().zip(&self.snake_positions).enumerate() {
+            let (head_color, body_color) = TextureCache::snake_colors(index);
+            let fade = if snake.alive { 1.0 } else { 0.35 };
+            let positions = pool.get_slice();
+            let Some((&head, _)) = positions.split_last() else { continue };
+
+            for (i, pair) in positions.windows(2).enumerate() {
+                let amount = (200 - (i * 15).min(150)) as f32 / 255.0 * fade;
+                let color = Self::blend(background, body_color, amount);
+
+                for pos in self.wrapped_copies(pair[0]) {
+                    for next in self.wrapped_copies(pair[1]).filter(|next| next.distance(pos) <= 1.01) {
+                        let (from, to) = (center(pos), center(next));
+                        draw_line(from.x, from.y, to.x, to.y, size, color);
+                    }
+                    let c = center(pos);
+                    draw_circle(c.x, c.y, size / 2.0, color);
                 }
             }
+
+            for pos in self.wrapped_copies(head) {
+                let x = offset_x + pos.x * self.cell_size + padding;
+                let y = offset_y + pos.y * self.cell_size + padding;
+                self.draw_snake_head(x, y, size, snake.direction, Color { a: fade, ..head_color });
+            }
         }
     }
 
-    fn draw_food(&self, offset_x: f32, offset_y: f32) {
-        let food_x = offset_x + self.food.x as f32 * self.cell_size + 2.0;
-        let food_y = offset_y + self.food.y as f32 * self.cell_size + 2.0;
-        let base_size = self.cell_size - 4.0;
+    fn wrapped_copies(&self, pos: Vec2) -> impl Iterator<Item = Vec2> {
+        let grid = vec2(self.simulation.grid_width() as f32, self.simulation.grid_height() as f32);
+        let shift = |v: f32, len: f32| if v < 0.0 { len } else if v > len - 1.0 { -len } else { 0.0 };
+        let shift = vec2(shift(pos.x, grid.x), shift(pos.y, grid.y));
+        std::iter::once(pos).chain((shift != Vec2::ZERO).then_some(pos + shift))
+    }
 
-        let pulse = (get_time() * 8.0).sin() as f32 * 0.1 + 1.0;
-        let food_size = base_size * pulse;
-        let offset = (base_size - food_size) / 2.0;
+    fn blend(background: Color, color: Color, amount: f32) -> Color {
+        Color::new(
+            background.r + (color.r - background.r) * amount,
+            background.g + (color.g - background.g) * amount,
+            background.b + (color.b - background.b) * amount,
+            1.0,
+        )
+    }
+
+    fn draw_snake_head(&self, x: f32, y: f32, size: f32, direction: Direction, color: Color) {
+        if let Some(texture) = &self.texture_cache.snake_head {
+            let rotation = match direction {
+                Direction::Right => 0.0,
+                Direction::Down => std::f32::consts::PI / 2.0,
+                Direction::Left => std::f32::consts::PI,
+                Direction::Up => -std::f32::consts::PI / 2.0,
+            };
 
-        if let Some(texture) = &self.texture_cache.food {
             draw_texture_ex(
                 texture,
-                food_x + offset,
-                food_y + offset,
-                WHITE,
+                x,
+                y,
+                color,
                 DrawTextureParams {
-                    dest_size: Some(Vec2::new(food_size, food_size)),
+                    dest_size: Some(Vec2::new(size, size)),
+                    rotation,
                     ..Default::default()
                 },
             );
         } else {
-            draw_rectangle(food_x + offset, food_y + offset, food_size, food_size,
-                           Color::from_rgba(255, 100, 100, 255));
+            draw_rectangle(x, y, size, size, color);
+        }
+    }
+
+    fn draw_food(&self, offset_x: f32, offset_y: f32) {
+        let padding = self.cell_padding();
+        let base_size = self.cell_size - padding * 2.0;
+        let pulse = (get_time() * 8.0).sin() as f32 * 0.1 + 1.0;
+        let food_size = base_size * pulse;
+        let offset = (base_size - food_size) / 2.0;
+
+        for food in self.simulation.foods() {
+            let expiring = food
+                .expires_at
+                .is_some_and(|expires_at| expires_at.saturating_sub(self.simulation.tick()) < 10);
+            if expiring && (get_time() * 10.0) as i64 % 2 == 0 {
+                continue;
+            }
+
+            let food_x = offset_x + food.position.x as f32 * self.cell_size + padding;
+            let food_y = offset_y + food.position.y as f32 * self.cell_size + padding;
+
+            if let Some(texture) = self.texture_cache.food_texture(food.kind) {
+                draw_texture_ex(
+                    texture,
+                    food_x + offset,
+                    food_y + offset,
+                    WHITE,
+                    DrawTextureParams {
+                        dest_size: Some(Vec2::new(food_size, food_size)),
+                        ..Default::default()
+                    },
+                );
+            } else {
+                draw_rectangle(food_x + offset, food_y + offset, food_size, food_size,
+                               TextureCache::food_color(food.kind));
+            }
+        }
+    }
+
+    fn draw_powerups(&self, offset_x: f32, offset_y: f32) {
+        let padding = self.cell_padding() / 2.0;
+        let size = self.cell_size - padding * 2.0;
+
+        for powerup in self.simulation.powerups() {
+            let expiring = powerup.expires_at.saturating_sub(self.simulation.tick()) < 10;
+            if expiring && (get_time() * 10.0) as i64 % 2 == 0 {
+                continue;
+            }
+
+            let x = offset_x + powerup.position.x as f32 * self.cell_size + padding;
+            let y = offset_y + powerup.position.y as f32 * self.cell_size + padding;
+
+            if let Some(texture) = self.texture_cache.powerup_texture(powerup.kind) {
+                draw_texture_ex(
+                    texture,
+                    x,
+                    y,
+                    WHITE,
+                    DrawTextureParams {
+                        dest_size: Some(Vec2::new(size, size)),
+                        ..Default::default()
+                    },
+                );
+            } else {
+                draw_rectangle(x, y, size, size, TextureCache::powerup_color(powerup.kind));
+            }
+        }
+    }
+
+    fn draw_effects(&self, snake: &Snake, mut x: f32, y: f32) {
+        let icon_size = 28.0;
+
+        for effect in &snake.effects {
+            let color = TextureCache::powerup_color(effect.kind);
+            let fraction = effect.remaining as f32 / effect.kind.duration() as f32;
+            draw_rectangle(x, y, icon_size, icon_size, Color::from_rgba(40, 40, 40, 255));
+            draw_rectangle(x, y + icon_size * (1.0 - fraction), icon_size, icon_size * fraction, color);
+            draw_rectangle_lines(x, y, icon_size, icon_size, 2.0, WHITE);
+
+            let icon = effect.kind.icon();
+            let dims = measure_text(icon, None, 20, 1.0);
+            draw_text(icon, x + (icon_size - dims.width) / 2.0, y + 20.0, 20.0, BLACK);
+
+            let seconds = effect.remaining as f64 * self.simulation.move_interval();
+            draw_text(&format!("{:.1}", seconds), x, y + icon_size + 16.0, 16.0, color);
+
+            x += icon_size + 12.0;
         }
     }
 
     fn draw_ui(&self) {
-        draw_text(&format!("Ball: {}", self.score), 20.0, 30.0, 24.0, WHITE);
-        draw_text(&format!("Uzunlik: {}", self.snake.len()), 20.0, 60.0, 24.0, WHITE);
-        draw_text(&format!("Rekord: {}", self.high_score), screen_width() - 150.0, 30.0, 24.0, YELLOW);
+        if self.input_buffers.len() > 1 {
+            self.draw_versus_ui();
+        } else {
+            let player = self.simulation.player();
+            draw_text(&format!("Ball: {}", player.score), 20.0, 30.0, 24.0, WHITE);
+            draw_text(&format!("Uzunlik: {}", player.body.len()), 20.0, 60.0, 24.0, WHITE);
+            let high_score = self.high_score(&self.run_mode_key());
+            draw_text(&format!("Rekord: {}", high_score), screen_width() - 150.0, 30.0, 24.0, YELLOW);
+            let effects_y = if self.current_stage.is_some() { 130.0 } else { 105.0 };
+            self.draw_effects(player, 20.0, effects_y);
+        }
+
+        let mode_text = format!("{} | {}", self.run_difficulty.label(), self.simulation.wall_mode().label());
+        draw_text(&mode_text, 20.0, 90.0, 20.0, GRAY);
+        if let Some(stage) = self.current_stage {
+            let goal_text = format!("Bosqich {}: {}", stage + 1, STAGES[stage].goal.label());
+            draw_text(&goal_text, 20.0, 115.0, 20.0, ORANGE);
+        }
 
         draw_text(&format!("FPS: {:.0}", get_fps()), screen_width() - 150.0, 60.0, 20.0, GREEN);
-        draw_text(&format!("Bufer: {}", self.input_buffer.commands.len()), screen_width() - 150.0, 90.0, 16.0, GRAY);
+        let buffered: Vec<String> = self.input_buffers.iter().map(|buffer| buffer.len().to_string()).collect();
+        draw_text(&format!("Bufer: {}", buffered.join("/")), screen_width() - 150.0, 90.0, 16.0, GRAY);
+
+        if self.simulation.slow_ticks() > 0 {
+            let slow_text = format!("Sekinlashish: {}", self.simulation.slow_ticks());
+            draw_text(&slow_text, screen_width() - 150.0, 115.0, 20.0, TextureCache::food_color(FoodKind::Slow));
+        }
+
+        self.draw_opponents_ui();
+
+        if self.autopilot.is_some() && self.replay_player.is_none() {
+            let text = format!("AVTOPILOT ({} - o'chirish)", self.controls.hint(Action::Autopilot));
+            let dims = measure_text(&text, None, 20, 1.0);
+            draw_text(&text, (screen_width() - dims.width) / 2.0, 20.0, 20.0, SKYBLUE);
+        }
+
+        if self.rewind.is_some() && self.state == GameState::Playing {
+            let text = format!("MASHQ ({} - orqaga)", self.controls.hint(Action::Rewind));
+            let dims = measure_text(&text, None, 20, 1.0);
+            draw_text(&text, (screen_width() - dims.width) / 2.0, 42.0, 20.0, ORANGE);
+        }
+    }
+
+    fn draw_opponents_ui(&self) {
+        let humans = self.input_buffers.len();
+        for (i, snake) in self.simulation.snakes().iter().skip(humans).enumerate() {
+            let (color, _) = TextureCache::snake_colors(humans + i);
+            let text = format!("Raqib {}: {} ball | uzunlik {}", i + 1, snake.score, snake.body.len());
+            let y = screen_height() - 20.0 - (self.ai_controllers.len() - 1 - i) as f32 * 22.0;
+            draw_text(&text, 20.0, y, 20.0, if snake.alive { color } else { GRAY });
+        }
+    }
+
+    fn draw_versus_ui(&self) {
+        for (index, snake) in self.simulation.snakes().iter().take(VERSUS_PLAYERS).enumerate() {
+            let (color, _) = TextureCache::snake_colors(index);
+            let y = 30.0 + index as f32 * 30.0;
+            let text = format!(
+                "{}-o'yinchi: {} ball | uzunlik {} | g'alaba {}",
+                index + 1,
+                snake.score,
+                snake.body.len(),
+                self.versus_wins[index]
+            );
+            draw_text(&text, 20.0, y, 22.0, if snake.alive { color } else { GRAY });
+            self.draw_effects(snake, 20.0 + index as f32 * 200.0, 105.0);
+        }
     }
 
     fn draw_pause_overlay(&self) {
@@ -451,9 +1538,15 @@ impl Game {
         let dims = measure_text(text, None, 48, 1.0);
         draw_text(text, (screen_width - dims.width) / 2.0, screen_height / 2.0 - 20.0, 48.0, WHITE);
 
-        let instruction = "ESC - davom etish";
-        let dims2 = measure_text(instruction, None, 24, 1.0);
-        draw_text(instruction, (screen_width - dims2.width) / 2.0, screen_height / 2.0 + 30.0, 24.0, GRAY);
+        let instruction = format!(
+            "{} - davom etish | {} - saqlash va chiqish",
+            self.controls.hint(Action::Pause),
+            self.controls.hint(Action::SaveAndQuit)
+        );
+        let dims2 = measure_text(&instruction, None, 24, 1.0);
+        draw_text(&instruction, (screen_width - dims2.width) / 2.0, screen_height / 2.0 + 30.0, 24.0, GRAY);
+
+        self.draw_message(screen_height / 2.0 + 70.0);
     }
 
     fn draw_game_over(&self) {
@@ -462,22 +1555,388 @@ impl Game {
 
         draw_rectangle(0.0, 0.0, screen_width, screen_height, Color::from_rgba(0, 0, 0, 200));
 
-        let title = "O'YIN TUGADI!";
+        let title = if self.replay_player.is_some() { "TAKROR TUGADI" } else { "O'YIN TUGADI!" };
         let dims = measure_text(title, None, 48, 1.0);
         draw_text(title, (screen_width - dims.width) / 2.0, screen_height / 2.0 - 60.0, 48.0, RED);
 
-        let score_text = &format!("Yakuniy ball: {}", self.score);
+        let score_text = &format!("Yakuniy ball: {}", self.simulation.player().score);
         let dims2 = measure_text(score_text, None, 32, 1.0);
         draw_text(score_text, (screen_width - dims2.width) / 2.0, screen_height / 2.0 - 10.0, 32.0, WHITE);
 
-        if self.score == self.high_score && self.score > 0 {
+        let score = self.simulation.player().score;
+        let high_score = self.high_score(&self.run_mode_key());
+        if score == high_score && score > 0 && self.replay_player.is_none() {
             let record_text = "🎉 YANGI REKORD! 🎉";
             let dims3 = measure_text(record_text, None, 28, 1.0);
             draw_text(record_text, (screen_width - dims3.width) / 2.0, screen_height / 2.0 + 20.0, 28.0, GOLD);
         }
 
-        let instruction = "SPACE - qayta o'ynash | ESC - menuga";
+        let seed_text = &format!("Seed: {}", self.simulation.seed());
+        let dims_seed = measure_text(seed_text, None, 20, 1.0);
+        draw_text(seed_text, (screen_width - dims_seed.width) / 2.0, screen_height / 2.0 + 90.0, 20.0, GRAY);
+
+        let mut instruction = format!("{} - qayta o'ynash", self.controls.hint(Action::Confirm));
+        if self.rewind.is_some() {
+            instruction.push_str(&format!(" | {} - orqaga", self.controls.hint(Action::Rewind)));
+        }
+        if self.last_replay.is_some() {
+            instruction.push_str(&format!(" | {} - takrorni ko'rish", self.controls.hint(Action::WatchReplay)));
+        }
+        instruction.push_str(&format!(" | {} - menuga", self.controls.hint(Action::Back)));
+        let dims4 = measure_text(&instruction, None, 20, 1.0);
+        draw_text(&instruction, (screen_width - dims4.width) / 2.0, screen_height / 2.0 + 60.0, 20.0, GRAY);
+
+        self.draw_message(screen_height / 2.0 + 130.0);
+        ui::draw_buttons(&self.game_over_buttons());
+    }
+
+    fn draw_replay_overlay(&self) {
+        let text = "▶ TAKROR";
+        let dims = measure_text(text, None, 32, 1.0);
+        draw_text(text, (screen_width() - dims.width) / 2.0, 40.0, 32.0, ORANGE);
+
+        let instruction = format!("{} - menuga", self.controls.hint(Action::Back));
+        let dims2 = measure_text(&instruction, None, 20, 1.0);
+        draw_text(&instruction, (screen_width() - dims2.width) / 2.0, 65.0, 20.0, GRAY);
+    }
+
+    fn draw_rewind_overlay(&self) {
+        let text = "◀◀ ORQAGA";
+        let dims = measure_text(text, None, 32, 1.0);
+        draw_text(text, (screen_width() - dims.width) / 2.0, 40.0, 32.0, SKYBLUE);
+
+        let instruction = match &self.rewind {
+            Some(rewind) if !rewind.is_empty() => format!("{} qadam qoldi", rewind.len()),
+            _ => "Boshqa orqaga qaytib bo'lmaydi".to_string(),
+        };
+        let dims2 = measure_text(&instruction, None, 20, 1.0);
+        draw_text(&instruction, (screen_width() - dims2.width) / 2.0, 65.0, 20.0, GRAY);
+    }
+
+    fn draw_victory(&self) {
+        let screen_width = screen_width();
+        let screen_height = screen_height();
+
+        draw_rectangle(0.0, 0.0, screen_width, screen_height, Color::from_rgba(0, 0, 0, 200));
+
+        let title = if self.replay_player.is_some() { "TAKROR TUGADI" } else { "🏆 G'ALABA! 🏆" };
+        let dims = measure_text(title, None, 48, 1.0);
+        draw_text(title, (screen_width - dims.width) / 2.0, screen_height / 2.0 - 90.0, 48.0, GOLD);
+
+        let subtitle = "Ilon butun maydonni egalladi!";
+        let dims1 = measure_text(subtitle, None, 24, 1.0);
+        draw_text(subtitle, (screen_width - dims1.width) / 2.0, screen_height / 2.0 - 50.0, 24.0, GREEN);
+
+        let score_text = &format!("Yakuniy ball: {}", self.simulation.player().score);
+        let dims2 = measure_text(score_text, None, 32, 1.0);
+        draw_text(score_text, (screen_width - dims2.width) / 2.0, screen_height / 2.0 - 10.0, 32.0, WHITE);
+
+        let bonus_text = &format!("G'alaba bonusi: +{}", VICTORY_BONUS);
+        let dims3 = measure_text(bonus_text, None, 24, 1.0);
+        draw_text(bonus_text, (screen_width - dims3.width) / 2.0, screen_height / 2.0 + 20.0, 24.0, GOLD);
+
+        let mut instruction = format!("{} - qayta o'ynash", self.controls.hint(Action::Confirm));
+        if self.last_replay.is_some() {
+            instruction.push_str(&format!(" | {} - takrorni ko'rish", self.controls.hint(Action::WatchReplay)));
+        }
+        instruction.push_str(&format!(" | {} - menuga", self.controls.hint(Action::Back)));
+        let dims4 = measure_text(&instruction, None, 20, 1.0);
+        draw_text(&instruction, (screen_width - dims4.width) / 2.0, screen_height / 2.0 + 60.0, 20.0, GRAY);
+
+        self.draw_message(screen_height / 2.0 + 130.0);
+        ui::draw_buttons(&self.game_over_buttons());
+    }
+
+    fn draw_level_select(&self) {
+        let screen_width = screen_width();
+        let screen_height = screen_height();
+
+        let title = "KAMPANIYA";
+        let dims = measure_text(title, None, 48, 1.0);
+        draw_text(title, (screen_width - dims.width) / 2.0, screen_height / 2.0 - 150.0, 48.0, GREEN);
+
+        for (i, stage) in STAGES.iter().enumerate() {
+            let locked = i > self.campaign_progress.unlocked;
+            let text = if locked {
+                format!("{}. {} - yopiq", i + 1, stage.level)
+            } else {
+                format!("{}. {} ({}) - {}", i + 1, stage.level, stage.wall_mode.label(), stage.goal.label())
+            };
+            let color = if locked {
+                DARKGRAY
+            } else if i == self.selected_stage {
+                YELLOW
+            } else {
+                WHITE
+            };
+
+            let y = screen_height / 2.0 - 90.0 + i as f32 * 36.0;
+            let dims = measure_text(&text, None, 24, 1.0);
+            draw_text(&text, (screen_width - dims.width) / 2.0, y, 24.0, color);
+        }
+
+        let instruction = format!(
+            "{}/{} - tanlash | {} - boshlash | {} - menuga",
+            self.controls.hint(Action::TurnUp),
+            self.controls.hint(Action::TurnDown),
+            self.controls.hint(Action::Confirm),
+            self.controls.hint(Action::Back)
+        );
+        let dims2 = measure_text(&instruction, None, 20, 1.0);
+        draw_text(&instruction, (screen_width - dims2.width) / 2.0, screen_height - 60.0, 20.0, GRAY);
+
+        self.draw_message(screen_height - 30.0);
+    }
+
+    fn draw_stage_complete(&self) {
+        let screen_width = screen_width();
+        let screen_height = screen_height();
+
+        draw_rectangle(0.0, 0.0, screen_width, screen_height, Color::from_rgba(0, 0, 0, 200));
+
+        let stage = self.current_stage.unwrap_or(0);
+        let last_stage = stage + 1 >= STAGES.len() || self.replay_player.is_some();
+
+        let title = if self.replay_player.is_some() {
+            "TAKROR TUGADI".to_string()
+        } else if last_stage {
+            "🏆 KAMPANIYA YAKUNLANDI! 🏆".to_string()
+        } else {
+            format!("BOSQICH {} O'TILDI!", stage + 1)
+        };
+        let dims = measure_text(&title, None, 48, 1.0);
+        draw_text(&title, (screen_width - dims.width) / 2.0, screen_height / 2.0 - 60.0, 48.0, GOLD);
+
+        let score_text = &format!("Ball: {}", self.simulation.player().score);
+        let dims2 = measure_text(score_text, None, 32, 1.0);
+        draw_text(score_text, (screen_width - dims2.width) / 2.0, screen_height / 2.0 - 10.0, 32.0, WHITE);
+
+        if !last_stage {
+            let next = &STAGES[stage + 1];
+            let next_text = &format!("Keyingi: {} - {}", next.level, next.goal.label());
+            let dims3 = measure_text(next_text, None, 24, 1.0);
+            draw_text(next_text, (screen_width - dims3.width) / 2.0, screen_height / 2.0 + 25.0, 24.0, GREEN);
+        }
+
+        let (confirm, back) = (self.controls.hint(Action::Confirm), self.controls.hint(Action::Back));
+        let instruction = if last_stage {
+            format!("{} / {} - bosqichlar", confirm, back)
+        } else {
+            format!("{} - keyingi bosqich | {} - bosqichlar", confirm, back)
+        };
+        let dims4 = measure_text(&instruction, None, 20, 1.0);
+        draw_text(&instruction, (screen_width - dims4.width) / 2.0, screen_height / 2.0 + 60.0, 20.0, GRAY);
+
+        self.draw_message(screen_height / 2.0 + 130.0);
+    }
+
+    fn draw_opponents(&self) {
+        let screen_width = screen_width();
+        let screen_height = screen_height();
+
+        let title = "RAQIBLAR";
+        let dims = measure_text(title, None, 48, 1.0);
+        draw_text(title, (screen_width - dims.width) / 2.0, screen_height / 2.0 - 120.0, 48.0, GREEN);
+
+        let level = self.ai_level as usize;
+        let slider = format!("{}{}", "■".repeat(level), "□".repeat(ai::MAX_LEVEL as usize - level));
+        let rows = [
+            format!("Soni: {}", self.opponents),
+            format!("Strategiya: {}", self.ai_strategy.label()),
+            format!("Daraja: {} {}/{}", slider, self.ai_level, ai::MAX_LEVEL),
+        ];
+
+        for (i, row) in rows.iter().enumerate() {
+            let y = screen_height / 2.0 - 40.0 + i as f32 * 40.0;
+            let color = if i == self.opponent_row { YELLOW } else { WHITE };
+            let dims = measure_text(row, None, 28, 1.0);
+            draw_text(row, (screen_width - dims.width) / 2.0, y, 28.0, color);
+        }
+
+        let note = "Raqiblar bilan o'yin rekordlarga yozilmaydi";
+        let dims2 = measure_text(note, None, 20, 1.0);
+        draw_text(note, (screen_width - dims2.width) / 2.0, screen_height / 2.0 + 110.0, 20.0, GRAY);
+
+        let instruction = format!(
+            "{}/{} - tanlash | {}/{} - o'zgartirish | {} - menuga",
+            self.controls.hint(Action::TurnUp),
+            self.controls.hint(Action::TurnDown),
+            self.controls.hint(Action::TurnLeft),
+            self.controls.hint(Action::TurnRight),
+            self.controls.hint(Action::Back)
+        );
+        let dims3 = measure_text(&instruction, None, 20, 1.0);
+        draw_text(&instruction, (screen_width - dims3.width) / 2.0, screen_height - 60.0, 20.0, GRAY);
+    }
+
+    fn draw_controls(&self) {
+        let screen_width = screen_width();
+        let screen_height = screen_height();
+
+        let title = "BOSHQARUV";
+        let dims = measure_text(title, None, 40, 1.0);
+        draw_text(title, (screen_width - dims.width) / 2.0, 60.0, 40.0, GREEN);
+
+        let schemes: Vec<String> = (0..SCHEME_PLAYERS)
+            .map(|player| format!("{}-o'yinchi: {}", player + 1, self.controls.scheme(player).label()))
+            .collect();
+        let schemes = schemes.join(" | ");
+        let dims_schemes = measure_text(&schemes, None, 18, 1.0);
+        draw_text(&schemes, (screen_width - dims_schemes.width) / 2.0, 85.0, 18.0, SKYBLUE);
+
+        let visible = (((screen_height - 210.0) / 30.0) as usize).max(1);
+        let first = (self.controls_row + 1).saturating_sub(visible);
+        for (row, &action) in Action::ALL.iter().enumerate().skip(first).take(visible) {
+            let y = 110.0 + (row - first) as f32 * 30.0;
+            let selected = row == self.controls_row;
+            draw_text(action.label(), 80.0, y, 22.0, if selected { YELLOW } else { WHITE });
+
+            let keys = self.controls.keys(action);
+            for slot in 0..KEYS_PER_ACTION {
+                let text = if selected && slot == self.controls_slot && self.rebinding {
+                    "...".to_string()
+                } else {
+                    keys.get(slot).map_or_else(|| "-".to_string(), |&key| controls::key_name(key))
+                };
+                let x = screen_width - 300.0 + slot as f32 * 130.0;
+                if selected && slot == self.controls_slot {
+                    draw_rectangle_lines(x - 8.0, y - 20.0, 120.0, 28.0, 2.0, YELLOW);
+                }
+                draw_text(&text, x, y, 22.0, if keys.is_empty() { GRAY } else { SKYBLUE });
+            }
+        }
+
+        self.draw_message(screen_height - 80.0);
+
+        let instructions: &[&str] = if self.rebinding {
+            &["Yangi tugmani bosing"]
+        } else {
+            &[
+                "Strelkalar - tanlash | Enter - o'zgartirish | Delete - o'chirish",
+                "1/2 - mutlaq/nisbiy boshqaruv | F5 - standart | ESC - menyu",
+            ]
+        };
+        for (i, instruction) in instructions.iter().enumerate() {
+            let dims2 = measure_text(instruction, None, 20, 1.0);
+            let y = screen_height - 50.0 + i as f32 * 24.0;
+            draw_text(instruction, (screen_width - dims2.width) / 2.0, y, 20.0, GRAY);
+        }
+    }
+
+    fn draw_round_over(&self) {
+        let screen_width = screen_width();
+        let screen_height = screen_height();
+
+        draw_rectangle(0.0, 0.0, screen_width, screen_height, Color::from_rgba(0, 0, 0, 200));
+
+        let (title, color) = match self.round_winner {
+            Some(winner) => (format!("{}-O'YINCHI G'OLIB!", winner + 1), TextureCache::snake_colors(winner).0),
+            None => ("DURANG!".to_string(), WHITE),
+        };
+        let dims = measure_text(&title, None, 48, 1.0);
+        draw_text(&title, (screen_width - dims.width) / 2.0, screen_height / 2.0 - 60.0, 48.0, color);
+
+        for (index, snake) in self.simulation.snakes().iter().take(VERSUS_PLAYERS).enumerate() {
+            let text = format!("{}-o'yinchi: {} ball | g'alabalar: {}", index + 1, snake.score, self.versus_wins[index]);
+            let dims2 = measure_text(&text, None, 28, 1.0);
+            let y = screen_height / 2.0 - 10.0 + index as f32 * 34.0;
+            draw_text(&text, (screen_width - dims2.width) / 2.0, y, 28.0, TextureCache::snake_colors(index).0);
+        }
+
+        let instruction = format!(
+            "{} - keyingi raund | {} - menuga",
+            self.controls.hint(Action::Confirm),
+            self.controls.hint(Action::Back)
+        );
+        let dims3 = measure_text(&instruction, None, 20, 1.0);
+        draw_text(&instruction, (screen_width - dims3.width) / 2.0, screen_height / 2.0 + 90.0, 20.0, GRAY);
+    }
+
+    fn draw_name_entry(&self) {
+        let screen_width = screen_width();
+        let screen_height = screen_height();
+
+        draw_rectangle(0.0, 0.0, screen_width, screen_height, Color::from_rgba(0, 0, 0, 200));
+
+        let title = "🎉 YANGI REKORD! 🎉";
+        let dims = measure_text(title, None, 48, 1.0);
+        draw_text(title, (screen_width - dims.width) / 2.0, screen_height / 2.0 - 60.0, 48.0, GOLD);
+
+        let score_text = &format!("Ball: {}", self.simulation.player().score);
+        let dims2 = measure_text(score_text, None, 32, 1.0);
+        draw_text(score_text, (screen_width - dims2.width) / 2.0, screen_height / 2.0 - 10.0, 32.0, WHITE);
+
+        let mut name = self.name_input.clone();
+        while name.len() < NAME_LENGTH {
+            name.push('_');
+        }
+        let name_text = &format!("Ismingiz: {}", name);
+        let dims3 = measure_text(name_text, None, 36, 1.0);
+        draw_text(name_text, (screen_width - dims3.width) / 2.0, screen_height / 2.0 + 35.0, 36.0, YELLOW);
+
+        let instruction = "Harflarni yozing | ENTER - saqlash";
         let dims4 = measure_text(instruction, None, 20, 1.0);
-        draw_text(instruction, (screen_width - dims4.width) / 2.0, screen_height / 2.0 + 60.0, 20.0, GRAY);
+        draw_text(instruction, (screen_width - dims4.width) / 2.0, screen_height / 2.0 + 75.0, 20.0, GRAY);
+    }
+
+    fn draw_leaderboard(&self) {
+        let screen_width = screen_width();
+        let screen_height = screen_height();
+
+        let title = &format!(
+            "REKORDLAR - {} | {}",
+            self.leaderboard_difficulty.label(),
+            self.leaderboard_mode.label()
+        );
+        let dims = measure_text(title, None, 40, 1.0);
+        draw_text(title, (screen_width - dims.width) / 2.0, 60.0, 40.0, GREEN);
+
+        let level = &self.levels[self.leaderboard_level];
+        let level_text = &format!("{} ({}x{})", level.name, level.width, level.height);
+        let dims1 = measure_text(level_text, None, 22, 1.0);
+        draw_text(level_text, (screen_width - dims1.width) / 2.0, 90.0, 22.0, WHITE);
+
+        let columns = [40.0, 90.0, 160.0, 260.0, 360.0, 450.0, 590.0];
+        let headers = ["#", "Ism", "Ball", "Uzunlik", "Vaqt", "Sana", "Seed"];
+        for (header, x) in headers.iter().zip(columns) {
+            draw_text(header, x, 120.0, 22.0, GRAY);
+        }
+
+        let key = self.level_mode_key(self.leaderboard_difficulty, self.leaderboard_mode, self.leaderboard_level);
+        let entries = self.leaderboard.entries(&key);
+        if entries.is_empty() {
+            let text = "Hali rekordlar yo'q";
+            let dims2 = measure_text(text, None, 24, 1.0);
+            draw_text(text, (screen_width - dims2.width) / 2.0, 170.0, 24.0, WHITE);
+        }
+
+        for (i, entry) in entries.iter().enumerate() {
+            let y = 155.0 + i as f32 * 30.0;
+            let cells = [
+                (i + 1).to_string(),
+                entry.name.clone(),
+                entry.score.to_string(),
+                entry.length.to_string(),
+                leaderboard::format_duration(entry.duration),
+                leaderboard::format_date(entry.date),
+                entry.seed.to_string(),
+            ];
+            let color = if i == 0 { GOLD } else { WHITE };
+            for (cell, x) in cells.iter().zip(columns) {
+                draw_text(cell, x, y, 22.0, color);
+            }
+        }
+
+        let instruction = format!(
+            "{}/{} - qiyinlik | {}/{} - rejim | {} - xarita | {} - menuga",
+            self.controls.hint(Action::TurnUp),
+            self.controls.hint(Action::TurnDown),
+            self.controls.hint(Action::TurnLeft),
+            self.controls.hint(Action::TurnRight),
+            self.controls.hint(Action::NextLevel),
+            self.controls.hint(Action::Back)
+        );
+        let dims3 = measure_text(&instruction, None, 20, 1.0);
+        draw_text(&instruction, (screen_width - dims3.width) / 2.0, screen_height - 30.0, 20.0, GRAY);
     }
 }
\ No newline at end of file
@@ -1,7 +1,17 @@
-#[derive(PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum GameState {
     Menu,
     Playing,
     GameOver,
+    Victory,
     Paused,
+    Replay,
+    LevelSelect,
+    StageComplete,
+    NameEntry,
+    Leaderboard,
+    RoundOver,
+    Opponents,
+    Rewinding,
+    Controls,
 }
\ No newline at end of file
@@ -0,0 +1,211 @@
+use crate::controls::Action;
+use crate::direction::Direction;
+
+pub const STICK_DEADZONE: f32 = 0.35;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PadButton {
+    Start,
+    Select,
+    South,
+    East,
+    West,
+    North,
+}
+
+impl PadButton {
+    pub fn for_action(action: Action) -> Option<PadButton> {
+        match action {
+            Action::Pause => Some(PadButton::Start),
+            Action::SaveAndQuit => Some(PadButton::Select),
+            Action::Confirm => Some(PadButton::South),
+            Action::Back => Some(PadButton::East),
+            Action::Rewind => Some(PadButton::West),
+            Action::Autopilot => Some(PadButton::North),
+            _ => None,
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "gamepad"), allow(dead_code))]
+pub fn stick_direction(x: f32, y: f32) -> Option<Direction> {
+    if x.abs().max(y.abs()) < STICK_DEADZONE {
+        None
+    } else if x.abs() > y.abs() {
+        Some(if x > 0.0 { Direction::Right } else { Direction::Left })
+    } else {
+        Some(if y > 0.0 { Direction::Up } else { Direction::Down })
+    }
+}
+
+#[cfg_attr(not(feature = "gamepad"), allow(dead_code))]
+struct Pad {
+    #[cfg(feature = "gamepad")]
+    id: gilrs::GamepadId,
+    pressed: Vec<PadButton>,
+    held: Vec<PadButton>,
+    turn: Option<Direction>,
+    stick: Option<Direction>,
+}
+
+pub struct Gamepads {
+    #[cfg(feature = "gamepad")]
+    gilrs: Option<gilrs::Gilrs>,
+    pads: Vec<Pad>,
+}
+
+impl Gamepads {
+    pub fn new() -> Self {
+        #[cfg(feature = "gamepad")]
+        {
+            let gilrs = match gilrs::Gilrs::new() {
+                Ok(gilrs) => Some(gilrs),
+                Err(err) => {
+                    eprintln!("geympadlarni ishga tushirib bo'lmadi: {}", err);
+                    None
+                }
+            };
+            let pads = gilrs
+                .iter()
+                .flat_map(|gilrs| gilrs.gamepads().map(|(id, _)| Pad::new(id)))
+                .collect();
+            Self { gilrs, pads }
+        }
+
+        #[cfg(not(feature = "gamepad"))]
+        Self { pads: Vec::new() }
+    }
+
+    pub fn update(&mut self) {
+        for pad in &mut self.pads {
+            pad.pressed.clear();
+            pad.turn = None;
+        }
+
+        #[cfg(feature = "gamepad")]
+        self.poll();
+    }
+
+    #[cfg(feature = "gamepad")]
+    fn poll(&mut self) {
+        use gilrs::{Axis, Button, EventType};
+
+        let Some(gilrs) = &mut self.gilrs else { return };
+        while let Some(event) = gilrs.next_event() {
+            let index = self.pads.iter().position(|pad| pad.id == event.id);
+            match (event.event, index) {
+                (EventType::Connected, None) => self.pads.push(Pad::new(event.id)),
+                (EventType::Disconnected, Some(index)) => {
+                    self.pads.remove(index);
+                }
+                (EventType::ButtonPressed(button, _), Some(index)) => {
+                    let pad = &mut self.pads[index];
+                    let direction = match button {
+                        Button::DPadUp => Some(Direction::Up),
+                        Button::DPadDown => Some(Direction::Down),
+                        Button::DPadLeft => Some(Direction::Left),
+                        Button::DPadRight => Some(Direction::Right),
+                        _ => None,
+                    };
+                    if direction.is_some() {
+                        pad.turn = direction;
+                    } else if let Some(button) = Self::map_button(button) {
+                        pad.pressed.push(button);
+                        pad.held.push(button);
+                    }
+                }
+                (EventType::ButtonReleased(button, _), Some(index)) => {
+                    if let Some(button) = Self::map_button(button) {
+                        self.pads[index].held.retain(|&held| held != button);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        for pad in &mut self.pads {
+            let gamepad = gilrs.gamepad(pad.id);
+            let stick = stick_direction(gamepad.value(Axis::LeftStickX), gamepad.value(Axis::LeftStickY));
+            if stick != pad.stick {
+                pad.stick = stick;
+                pad.turn = pad.turn.or(stick);
+            }
+        }
+    }
+
+    #[cfg(feature = "gamepad")]
+    fn map_button(button: gilrs::Button) -> Option<PadButton> {
+        use gilrs::Button;
+
+        match button {
+            Button::Start => Some(PadButton::Start),
+            Button::Select => Some(PadButton::Select),
+            Button::South => Some(PadButton::South),
+            Button::East => Some(PadButton::East),
+            Button::West => Some(PadButton::West),
+            Button::North => Some(PadButton::North),
+            _ => None,
+        }
+    }
+
+    pub fn action_pressed(&self, action: Action) -> bool {
+        PadButton::for_action(action).is_some_and(|button| self.pads.iter().any(|pad| pad.pressed.contains(&button)))
+    }
+
+    pub fn action_down(&self, action: Action) -> bool {
+        PadButton::for_action(action).is_some_and(|button| self.pads.iter().any(|pad| pad.held.contains(&button)))
+    }
+
+    pub fn turn(&self, pad: usize) -> Option<Direction> {
+        self.pads.get(pad).and_then(|pad| pad.turn)
+    }
+
+    pub fn any_turn(&self) -> Option<Direction> {
+        self.pads.iter().find_map(|pad| pad.turn)
+    }
+
+    pub fn any_input(&self) -> bool {
+        self.pads.iter().any(|pad| pad.turn.is_some() || !pad.pressed.is_empty())
+    }
+}
+
+#[cfg(feature = "gamepad")]
+impl Pad {
+    fn new(id: gilrs::GamepadId) -> Self {
+        Self {
+            id,
+            pressed: Vec::new(),
+            held: Vec::new(),
+            turn: None,
+            stick: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stick_ignores_the_deadzone() {
+        assert_eq!(stick_direction(0.0, 0.0), None);
+        assert_eq!(stick_direction(0.34, -0.34), None);
+        assert_eq!(stick_direction(STICK_DEADZONE, 0.0), Some(Direction::Right));
+        assert_eq!(stick_direction(0.0, -STICK_DEADZONE), Some(Direction::Down));
+    }
+
+    #[test]
+    fn stick_follows_the_dominant_axis() {
+        assert_eq!(stick_direction(0.8, 0.5), Some(Direction::Right));
+        assert_eq!(stick_direction(-0.8, 0.5), Some(Direction::Left));
+        assert_eq!(stick_direction(0.2, 0.9), Some(Direction::Up));
+        assert_eq!(stick_direction(-0.5, -0.6), Some(Direction::Down));
+    }
+
+    #[test]
+    fn pause_menu_actions_have_buttons() {
+        assert_eq!(PadButton::for_action(Action::Pause), Some(PadButton::Start));
+        assert_eq!(PadButton::for_action(Action::SaveAndQuit), Some(PadButton::Select));
+        assert_eq!(PadButton::for_action(Action::Back), Some(PadButton::East));
+    }
+}
@@ -0,0 +1,82 @@
+use crate::autopilot::Autopilot;
+use crate::config::GameConfig;
+use crate::level::Level;
+use crate::simulation::{SimEvent, Simulation};
+use crate::wall_mode::WallMode;
+
+pub struct Stats {
+    pub games: usize,
+    pub wins: usize,
+    pub total_score: i64,
+    pub best_score: i32,
+    pub total_length: usize,
+    pub total_ticks: u64,
+    pub stalled: usize,
+}
+
+pub fn run(config: &GameConfig, level: &Level, games: usize, seed: u64) -> Stats {
+    let rules = config.rules(WallMode::Solid);
+    let max_ticks = (level.width * level.height) as u32 * 200;
+    let mut stats = Stats {
+        games,
+        wins: 0,
+        total_score: 0,
+        best_score: 0,
+        total_length: 0,
+        total_ticks: 0,
+        stalled: 0,
+    };
+
+    for game in 0..games {
+        let mut simulation = Simulation::new(level, rules, seed.wrapping_add(game as u64));
+        let autopilot = Autopilot::new(&simulation);
+
+        loop {
+            if simulation.tick() >= max_ticks {
+                stats.stalled += 1;
+                break;
+            }
+
+            let input = autopilot.choose(&simulation, 0);
+            let events = simulation.step(&[input]);
+            if events.contains(&SimEvent::Won) {
+                stats.wins += 1;
+                break;
+            }
+            if events.contains(&SimEvent::Died(0)) {
+                break;
+            }
+        }
+
+        let player = simulation.player();
+        stats.total_score += player.score as i64;
+        stats.best_score = stats.best_score.max(player.score);
+        stats.total_length += player.body.len();
+        stats.total_ticks += simulation.tick() as u64;
+    }
+
+    stats
+}
+
+pub fn print(stats: &Stats, level: &Level) {
+    let games = stats.games.max(1) as f64;
+    println!("Xarita: {} ({}x{})", level.name, level.width, level.height);
+    println!("O'yinlar: {}", stats.games);
+    println!("G'alabalar: {} ({:.1}%)", stats.wins, stats.wins as f64 / games * 100.0);
+    println!("To'xtab qolgan: {}", stats.stalled);
+    println!("O'rtacha ball: {:.1}", stats.total_score as f64 / games);
+    println!("Eng yaxshi ball: {}", stats.best_score);
+    println!("O'rtacha uzunlik: {:.1}", stats.total_length as f64 / games);
+    println!("O'rtacha qadamlar: {:.1}", stats.total_ticks as f64 / games);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn autopilot_wins_every_seed_on_a_small_board() {
+        let stats = run(&GameConfig::default(), &Level::open(6, 5), 1000, 0);
+        assert_eq!(stats.wins, stats.games);
+    }
+}
@@ -1,33 +1,130 @@
 use std::collections::VecDeque;
 use crate::direction::Direction;
 
+struct Command {
+    direction: Direction,
+    queued_at: f64,
+}
+
 pub struct InputBuffer {
-    pub commands: VecDeque<Direction>,
+    commands: VecDeque<Command>,
     max_size: usize,
+    max_age: f64,
 }
 
 impl InputBuffer {
-    pub fn new(max_size: usize) -> Self {
+    pub fn new(max_size: usize, max_age: f64) -> Self {
         Self {
             commands: VecDeque::with_capacity(max_size),
             max_size,
+            max_age,
         }
     }
 
-    pub fn add_command(&mut self, direction: Direction) {
-        if self.commands.len() >= self.max_size {
-            self.commands.pop_front();
+    pub fn add_command(&mut self, direction: Direction, heading: Direction, now: f64) -> bool {
+        let projected = self.projected_heading(heading);
+        if self.commands.len() >= self.max_size || direction == projected || direction.is_opposite(projected) {
+            return false;
         }
-        if self.commands.back() != Some(&direction) {
-            self.commands.push_back(direction);
+
+        self.commands.push_back(Command { direction, queued_at: now });
+        true
+    }
+
+    pub fn get_next_command(&mut self, heading: Direction, now: f64) -> Option<Direction> {
+        while self.commands.front().is_some_and(|command| now - command.queued_at > self.max_age) {
+            self.commands.pop_front();
         }
+
+        let mut projected = heading;
+        self.commands.retain(|command| {
+            let valid = command.direction != projected && !command.direction.is_opposite(projected);
+            if valid {
+                projected = command.direction;
+            }
+            valid
+        });
+
+        self.commands.pop_front().map(|command| command.direction)
     }
 
-    pub fn get_next_command(&mut self) -> Option<Direction> {
-        self.commands.pop_front()
+    pub fn projected_heading(&self, heading: Direction) -> Direction {
+        self.commands.back().map_or(heading, |command| command.direction)
+    }
+
+    pub fn directions(&self) -> impl Iterator<Item = Direction> + '_ {
+        self.commands.iter().map(|command| command.direction)
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
     }
 
     pub fn clear(&mut self) {
         self.commands.clear();
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn turns_are_checked_against_the_projected_heading() {
+        let mut buffer = InputBuffer::new(3, 1.0);
+
+        assert!(buffer.add_command(Direction::Up, Direction::Right, 0.0));
+        assert!(!buffer.add_command(Direction::Up, Direction::Right, 0.0));
+        assert!(buffer.add_command(Direction::Right, Direction::Right, 0.0));
+        assert_eq!(buffer.projected_heading(Direction::Right), Direction::Right);
+        assert_eq!(buffer.len(), 2);
+    }
+
+    #[test]
+    fn two_queued_turns_cannot_reverse_in_one_step() {
+        let mut buffer = InputBuffer::new(3, 1.0);
+
+        assert!(buffer.add_command(Direction::Up, Direction::Right, 0.0));
+        assert!(!buffer.add_command(Direction::Down, Direction::Right, 0.0));
+        assert!(buffer.add_command(Direction::Left, Direction::Right, 0.0));
+
+        assert_eq!(buffer.get_next_command(Direction::Right, 0.0), Some(Direction::Up));
+        assert_eq!(buffer.get_next_command(Direction::Up, 0.0), Some(Direction::Left));
+        assert_eq!(buffer.get_next_command(Direction::Left, 0.0), None);
+    }
+
+    #[test]
+    fn full_buffer_rejects_new_turns() {
+        let mut buffer = InputBuffer::new(2, 1.0);
+
+        assert!(buffer.add_command(Direction::Up, Direction::Right, 0.0));
+        assert!(buffer.add_command(Direction::Left, Direction::Right, 0.0));
+        assert!(!buffer.add_command(Direction::Down, Direction::Right, 0.0));
+        assert_eq!(buffer.directions().collect::<Vec<_>>(), [Direction::Up, Direction::Left]);
+    }
+
+    #[test]
+    fn stale_commands_expire() {
+        let mut buffer = InputBuffer::new(3, 0.5);
+        buffer.add_command(Direction::Up, Direction::Right, 0.0);
+        assert_eq!(buffer.get_next_command(Direction::Right, 0.5), Some(Direction::Up));
+
+        buffer.add_command(Direction::Down, Direction::Right, 1.0);
+        assert_eq!(buffer.get_next_command(Direction::Right, 1.6), None);
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn turn_left_behind_by_an_expired_command_is_dropped() {
+        let mut buffer = InputBuffer::new(3, 0.5);
+        buffer.add_command(Direction::Up, Direction::Right, 0.0);
+        buffer.add_command(Direction::Left, Direction::Right, 0.4);
+
+        assert_eq!(buffer.get_next_command(Direction::Right, 0.6), None);
+        assert!(buffer.is_empty());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::storage;
+
+pub const MAX_ENTRIES: usize = 10;
+pub const NAME_LENGTH: usize = 3;
+
+#[derive(Debug, Clone)]
+pub struct LeaderboardEntry {
+    pub name: String,
+    pub score: i32,
+    pub length: usize,
+    pub duration: f64,
+    pub date: u64,
+    pub seed: u64,
+}
+
+pub struct Leaderboard {
+    entries: HashMap<String, Vec<LeaderboardEntry>>,
+}
+
+impl LeaderboardEntry {
+    fn to_line(&self, mode: &str) -> String {
+        format!(
+            "{}\t{}\t{}\t{}\t{:.1}\t{}\t{}",
+            mode, self.name, self.score, self.length, self.duration, self.date, self.seed
+        )
+    }
+
+    fn from_line(line: &str) -> Option<(String, LeaderboardEntry)> {
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() != 7 {
+            return None;
+        }
+
+        let entry = LeaderboardEntry {
+            name: fields[1].to_string(),
+            score: fields[2].parse().ok()?,
+            length: fields[3].parse().ok()?,
+            duration: fields[4].parse().ok()?,
+            date: fields[5].parse().ok()?,
+            seed: fields[6].parse().ok()?,
+        };
+        Some((fields[0].to_string(), entry))
+    }
+}
+
+impl Leaderboard {
+    pub fn load() -> Self {
+        let mut leaderboard = Leaderboard { entries: HashMap::new() };
+
+        if let Ok(contents) = fs::read_to_string(Self::path()) {
+            for line in contents.lines().filter(|line| !line.starts_with('#')) {
+                if let Some((mode, entry)) = LeaderboardEntry::from_line(line) {
+                    leaderboard.insert(&mode, entry);
+                }
+            }
+        }
+
+        leaderboard
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut modes: Vec<&String> = self.entries.keys().collect();
+        modes.sort();
+
+        let mut contents = String::from("# mode\tname\tscore\tlength\tduration\tdate\tseed\n");
+        for mode in modes {
+            for entry in &self.entries[mode] {
+                contents.push_str(&entry.to_line(mode));
+                contents.push('\n');
+            }
+        }
+
+        fs::write(path, contents)
+    }
+
+    pub fn entries(&self, mode: &str) -> &[LeaderboardEntry] {
+        self.entries.get(mode).map_or(&[], |entries| entries.as_slice())
+    }
+
+    pub fn best(&self, mode: &str) -> i32 {
+        self.entries(mode).first().map_or(0, |entry| entry.score)
+    }
+
+    pub fn qualifies(&self, mode: &str, score: i32) -> bool {
+        let entries = self.entries(mode);
+        score > 0 && (entries.len() < MAX_ENTRIES || entries.last().is_some_and(|last| score > last.score))
+    }
+
+    pub fn insert(&mut self, mode: &str, entry: LeaderboardEntry) {
+        let entries = self.entries.entry(mode.to_string()).or_default();
+        let rank = entries.partition_point(|existing| existing.score >= entry.score);
+        entries.insert(rank, entry);
+        entries.truncate(MAX_ENTRIES);
+    }
+
+    fn path() -> PathBuf {
+        storage::data_dir().join("leaderboard.tsv")
+    }
+}
+
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+pub fn format_date(unix: u64) -> String {
+    let days = (unix / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+pub fn format_duration(seconds: f64) -> String {
+    let total = seconds as u64;
+    format!("{}:{:02}", total / 60, total % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODE: &str = "normal-solid-Ochiq maydon-25x20";
+
+    fn entry(name: &str, score: i32) -> LeaderboardEntry {
+        LeaderboardEntry { name: name.to_string(), score, length: 5, duration: 12.0, date: 0, seed: 1 }
+    }
+
+    fn names(leaderboard: &Leaderboard) -> Vec<&str> {
+        leaderboard.entries(MODE).iter().map(|entry| entry.name.as_str()).collect()
+    }
+
+    #[test]
+    fn insert_keeps_scores_descending() {
+        let mut leaderboard = Leaderboard { entries: HashMap::new() };
+        for (name, score) in [("AAA", 30), ("BBB", 90), ("CCC", 60)] {
+            leaderboard.insert(MODE, entry(name, score));
+        }
+
+        assert_eq!(names(&leaderboard), ["BBB", "CCC", "AAA"]);
+        assert_eq!(leaderboard.best(MODE), 90);
+        assert!(leaderboard.entries("hard-solid-Ochiq maydon-25x20").is_empty());
+    }
+
+    #[test]
+    fn only_the_top_entries_are_kept() {
+        let mut leaderboard = Leaderboard { entries: HashMap::new() };
+        for score in 1..=MAX_ENTRIES as i32 + 3 {
+            leaderboard.insert(MODE, entry("AAA", score * 10));
+        }
+
+        let entries = leaderboard.entries(MODE);
+        assert_eq!(entries.len(), MAX_ENTRIES);
+        assert_eq!(entries.last().unwrap().score, 40);
+        assert!(!leaderboard.qualifies(MODE, 30));
+        assert!(leaderboard.qualifies(MODE, 50));
+        assert!(!leaderboard.qualifies(MODE, 0));
+    }
+
+    #[test]
+    fn ties_rank_behind_earlier_entries() {
+        let mut leaderboard = Leaderboard { entries: HashMap::new() };
+        leaderboard.insert(MODE, entry("OLD", 50));
+        leaderboard.insert(MODE, entry("NEW", 50));
+        assert_eq!(names(&leaderboard), ["OLD", "NEW"]);
+
+        for _ in 2..MAX_ENTRIES {
+            leaderboard.insert(MODE, entry("MID", 70));
+        }
+        assert!(!leaderboard.qualifies(MODE, 50));
+    }
+
+    #[test]
+    fn lines_round_trip() {
+        let original = entry("XYZ", 120);
+        let (mode, parsed) = LeaderboardEntry::from_line(&original.to_line(MODE)).expect("line parses");
+
+        assert_eq!(mode, MODE);
+        assert_eq!((parsed.name.as_str(), parsed.score, parsed.seed), ("XYZ", 120, 1));
+        assert!(LeaderboardEntry::from_line("normal-solid\tXYZ\t120").is_none());
+    }
+
+    #[test]
+    fn formats_dates() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(951_782_400), "2000-02-29");
+        assert_eq!(format_date(1_700_000_000), "2023-11-14");
+        assert_eq!(format_duration(125.9), "2:05");
+    }
+}
@@ -0,0 +1,183 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use crate::position::Position;
+
+pub const DEFAULT_LEVEL: &str = "Ochiq maydon";
+
+const BUNDLED: [(&str, &str); 4] = [
+    ("Quti", include_str!("../levels/box.txt")),
+    ("Xoch", include_str!("../levels/cross.txt")),
+    ("Ustunlar", include_str!("../levels/pillars.txt")),
+    ("Yo'laklar", include_str!("../levels/corridors.txt")),
+];
+
+#[derive(Debug, Clone)]
+pub struct Level {
+    pub name: String,
+    pub width: i32,
+    pub height: i32,
+    pub walls: Vec<Position>,
+    pub spawn: Position,
+}
+
+#[derive(Debug)]
+pub enum LevelError {
+    Io(io::Error),
+    Empty,
+    UnexpectedChar { line: usize, column: usize, found: char },
+    RaggedRow { line: usize, column: usize, expected: usize },
+    DuplicateSpawn { line: usize, column: usize },
+    MissingSpawn,
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LevelError::Io(err) => write!(f, "fayl xatosi: {}", err),
+            LevelError::Empty => write!(f, "xarita bo'sh"),
+            LevelError::UnexpectedChar { line, column, found } => {
+                write!(f, "{}:{}: noma'lum belgi '{}'", line, column, found)
+            }
+            LevelError::RaggedRow { line, column, expected } => {
+                write!(f, "{}:{}: qator uzunligi {} bo'lishi kerak", line, column, expected)
+            }
+            LevelError::DuplicateSpawn { line, column } => {
+                write!(f, "{}:{}: boshlanish nuqtasi ikkinchi marta berilgan", line, column)
+            }
+            LevelError::MissingSpawn => write!(f, "boshlanish nuqtasi (S) topilmadi"),
+        }
+    }
+}
+
+impl From<io::Error> for LevelError {
+    fn from(err: io::Error) -> Self {
+        LevelError::Io(err)
+    }
+}
+
+impl Level {
+    pub fn open(width: i32, height: i32) -> Level {
+        Level {
+            name: DEFAULT_LEVEL.to_string(),
+            width,
+            height,
+            walls: Vec::new(),
+            spawn: Position { x: width / 2, y: height / 2 },
+        }
+    }
+
+    pub fn bundled(open_width: i32, open_height: i32) -> Vec<Level> {
+        std::iter::once(Level::open(open_width, open_height))
+            .chain(BUNDLED.iter().map(|(name, source)| {
+                Level::parse(name, source).expect("bundled level is valid")
+            }))
+            .collect()
+    }
+
+    pub fn load(path: &Path) -> Result<Level, LevelError> {
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        Level::parse(&name, &fs::read_to_string(path)?)
+    }
+
+    pub fn parse(name: &str, source: &str) -> Result<Level, LevelError> {
+        let mut width = None;
+        let mut height = 0;
+        let mut walls = Vec::new();
+        let mut spawn = None;
+
+        for (index, row) in source.lines().enumerate() {
+            let line = index + 1;
+            let row = row.trim_end();
+
+            if row.is_empty() || row.starts_with(';') {
+                continue;
+            }
+
+            let row_width = row.chars().count();
+            let expected = *width.get_or_insert(row_width);
+            if row_width != expected {
+                return Err(LevelError::RaggedRow {
+                    line,
+                    column: row_width.min(expected) + 1,
+                    expected,
+                });
+            }
+
+            for (x, c) in row.chars().enumerate() {
+                let column = x + 1;
+                let pos = Position { x: x as i32, y: height };
+                match c {
+                    '#' => walls.push(pos),
+                    '.' => {}
+                    'S' => {
+                        if spawn.is_some() {
+                            return Err(LevelError::DuplicateSpawn { line, column });
+                        }
+                        spawn = Some(pos);
+                    }
+                    _ => return Err(LevelError::UnexpectedChar { line, column, found: c }),
+                }
+            }
+
+            height += 1;
+        }
+
+        let width = width.ok_or(LevelError::Empty)?;
+
+        Ok(Level {
+            name: name.to_string(),
+            width: width as i32,
+            height,
+            walls,
+            spawn: spawn.ok_or(LevelError::MissingSpawn)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_walls_and_spawn() {
+        let level = Level::parse("test", "; izoh\n#####\n#S..#\n#####\n").expect("level parses");
+
+        assert_eq!((level.width, level.height), (5, 3));
+        assert_eq!(level.spawn, Position { x: 1, y: 1 });
+        assert_eq!(level.walls.len(), 12);
+    }
+
+    #[test]
+    fn bundled_levels_parse() {
+        assert_eq!(Level::bundled(25, 20).len(), BUNDLED.len() + 1);
+    }
+
+    #[test]
+    fn reports_unexpected_character_position() {
+        let err = Level::parse("test", "#####\n#S.x#\n#####").unwrap_err();
+        assert!(matches!(err, LevelError::UnexpectedChar { line: 2, column: 4, found: 'x' }));
+    }
+
+    #[test]
+    fn reports_ragged_row_position() {
+        let err = Level::parse("test", "#####\n#S..#\n###").unwrap_err();
+        assert!(matches!(err, LevelError::RaggedRow { line: 3, column: 4, expected: 5 }));
+
+        let err = Level::parse("test", "#####\n#S..#.\n#####").unwrap_err();
+        assert!(matches!(err, LevelError::RaggedRow { line: 2, column: 6, expected: 5 }));
+    }
+
+    #[test]
+    fn reports_missing_and_duplicate_spawn() {
+        assert!(matches!(Level::parse("test", "#####\n#...#\n#####"), Err(LevelError::MissingSpawn)));
+        assert!(matches!(
+            Level::parse("test", "#####\n#S.S#\n#####"),
+            Err(LevelError::DuplicateSpawn { line: 2, column: 4 })
+        ));
+    }
+}
@@ -6,13 +6,79 @@ mod vec2_pool;
 mod spatial_hash;
 mod texture_cache;
 mod input_buffer;
+mod simulation;
+mod cli;
+mod replay;
+mod storage;
+mod wall_mode;
+mod level;
+mod campaign;
+mod leaderboard;
+mod config;
+mod difficulty;
+mod food;
+mod powerup;
+mod ai;
+mod autopilot;
+mod headless;
+mod codec;
+mod savegame;
+mod rewind;
+mod controls;
+mod gamepad;
+mod pointer;
+mod ui;
 
 use macroquad::prelude::*;
 use game::Game;
+use cli::CliArgs;
+use level::Level;
+use config::GameConfig;
 
-#[macroquad::main("🐍 Ilon")]
-async fn main() {
-    let mut game = Game::new();
+fn main() {
+    let cli = match CliArgs::parse() {
+        Ok(cli) => cli,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(2);
+        }
+    };
+
+    let config = match load_config(&cli) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("sozlamalar xatosi: {}", err);
+            std::process::exit(2);
+        }
+    };
+
+    let mut levels = Level::bundled(config.grid_width, config.grid_height);
+    let mut level_index = 0;
+    if let Some(path) = &cli.level {
+        match Level::load(path) {
+            Ok(level) => {
+                levels.push(level);
+                level_index = levels.len() - 1;
+            }
+            Err(err) => {
+                eprintln!("{}: {}", path.display(), err);
+                std::process::exit(2);
+            }
+        }
+    }
+
+    if let Some(games) = cli.simulate {
+        let seed = cli.seed.unwrap_or_else(|| fastrand::u64(..));
+        let stats = headless::run(&config, &levels[level_index], games, seed);
+        headless::print(&stats, &levels[level_index]);
+        return;
+    }
+
+    macroquad::Window::from_config(Game::window_conf(&config), run(config, cli.seed, levels, level_index));
+}
+
+async fn run(config: GameConfig, seed: Option<u64>, levels: Vec<Level>, level_index: usize) {
+    let mut game = Game::new(config, seed, levels, level_index);
     game.initialize().await;
 
     loop {
@@ -22,4 +88,51 @@ async fn main() {
 
         next_frame().await
     }
-}
\ No newline at end of file
+}
+
+fn load_config(cli: &CliArgs) -> Result<GameConfig, config::ConfigError> {
+    let mut config = match &cli.config {
+        Some(path) => GameConfig::load(path)?,
+        None => {
+            let path = GameConfig::default_path();
+            if path.exists() {
+                GameConfig::load(&path)?
+            } else {
+                GameConfig::default()
+            }
+        }
+    };
+
+    for (key, value) in &cli.overrides {
+        config.set(key, value, None)?;
+    }
+
+    config.validate()?;
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_line_overrides_the_config_file() {
+        let path = std::env::temp_dir().join(format!("ilon-cli-test-{}.txt", std::process::id()));
+        std::fs::write(&path, "grid_width = 30\nmove_interval = 0.2\n").unwrap();
+        let path_arg = path.to_string_lossy().into_owned();
+        let cli = CliArgs::parse_from(["--config", &path_arg, "--grid-width", "40"].map(String::from))
+            .expect("arguments parse");
+
+        let config = load_config(&cli);
+        std::fs::remove_file(&path).unwrap();
+        let config = config.expect("config loads");
+        assert_eq!(config.grid_width, 40);
+        assert_eq!(config.move_interval, 0.2);
+    }
+
+    #[test]
+    fn invalid_override_is_rejected() {
+        let cli = CliArgs::parse_from(["--grid-width", "500"].map(String::from)).expect("arguments parse");
+        assert!(load_config(&cli).is_err());
+    }
+}
@@ -0,0 +1,65 @@
+use macroquad::prelude::*;
+use crate::direction::Direction;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gesture {
+    Swipe(Direction),
+    Tap(Vec2),
+}
+
+pub struct Pointer {
+    origin: Option<Vec2>,
+    swiped: bool,
+}
+
+impl Pointer {
+    pub fn new() -> Self {
+        Self { origin: None, swiped: false }
+    }
+
+    pub fn update(&mut self, min_swipe_distance: f32) -> Option<Gesture> {
+        let position = Vec2::from(mouse_position());
+        if is_mouse_button_pressed(MouseButton::Left) {
+            self.origin = Some(position);
+            self.swiped = false;
+        }
+        let origin = self.origin?;
+
+        if is_mouse_button_released(MouseButton::Left) {
+            self.origin = None;
+            return (!self.swiped).then_some(Gesture::Tap(position));
+        }
+
+        let delta = position - origin;
+        if delta.length() >= min_swipe_distance {
+            self.origin = Some(position);
+            self.swiped = true;
+            return Some(Gesture::Swipe(direction_towards(delta)));
+        }
+        None
+    }
+}
+
+pub fn direction_towards(delta: Vec2) -> Direction {
+    if delta.x.abs() > delta.y.abs() {
+        if delta.x > 0.0 { Direction::Right } else { Direction::Left }
+    } else if delta.y > 0.0 {
+        Direction::Down
+    } else {
+        Direction::Up
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn follows_the_dominant_axis_in_screen_space() {
+        assert_eq!(direction_towards(vec2(40.0, 10.0)), Direction::Right);
+        assert_eq!(direction_towards(vec2(-40.0, -10.0)), Direction::Left);
+        assert_eq!(direction_towards(vec2(10.0, 40.0)), Direction::Down);
+        assert_eq!(direction_towards(vec2(-10.0, -40.0)), Direction::Up);
+        assert_eq!(direction_towards(vec2(30.0, 30.0)), Direction::Down);
+    }
+}
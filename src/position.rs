@@ -1,5 +1,18 @@
+use crate::direction::Direction;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Position {
     pub x: i32,
     pub y: i32,
+}
+
+impl Position {
+    pub fn moved(self, direction: Direction) -> Position {
+        match direction {
+            Direction::Up => Position { x: self.x, y: self.y - 1 },
+            Direction::Down => Position { x: self.x, y: self.y + 1 },
+            Direction::Left => Position { x: self.x - 1, y: self.y },
+            Direction::Right => Position { x: self.x + 1, y: self.y },
+        }
+    }
 }
\ No newline at end of file
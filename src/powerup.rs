@@ -0,0 +1,49 @@
+use crate::position::Position;
+
+pub const POWERUP_LIFETIME: u32 = 60;
+pub const MAGNET_RADIUS: i32 = 5;
+pub const SCORE_MULTIPLIER: i32 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PowerUpKind {
+    Ghost,
+    Magnet,
+    Multiplier,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PowerUp {
+    pub position: Position,
+    pub kind: PowerUpKind,
+    pub expires_at: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ActiveEffect {
+    pub kind: PowerUpKind,
+    pub remaining: u32,
+}
+
+impl PowerUpKind {
+    pub const ALL: [PowerUpKind; 3] = [
+        PowerUpKind::Ghost,
+        PowerUpKind::Magnet,
+        PowerUpKind::Multiplier,
+    ];
+
+    pub fn duration(self) -> u32 {
+        match self {
+            PowerUpKind::Ghost => 100,
+            PowerUpKind::Magnet => 80,
+            PowerUpKind::Multiplier => 120,
+        }
+    }
+
+    pub fn icon(self) -> &'static str {
+        match self {
+            PowerUpKind::Ghost => "G",
+            PowerUpKind::Magnet => "M",
+            PowerUpKind::Multiplier => "x2",
+        }
+    }
+}
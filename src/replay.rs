@@ -0,0 +1,336 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use crate::direction::Direction;
+use crate::wall_mode::WallMode;
+use crate::simulation::Rules;
+use crate::level::Level;
+use crate::difficulty::Difficulty;
+use crate::campaign::STAGES;
+use crate::food::FoodChances;
+use crate::codec::{self, ByteReader, Truncated};
+use crate::config::GRID_SIZE;
+
+const MAGIC: &[u8; 4] = b"ILRP";
+const VERSION: u8 = 8;
+const NO_STAGE: u8 = u8::MAX;
+const ENTRY_SIZE: usize = 4 + 1;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReplayEntry {
+    pub tick: u32,
+    pub direction: Direction,
+}
+
+#[derive(Debug, Clone)]
+pub struct Replay {
+    pub seed: u64,
+    pub level: String,
+    pub grid_width: i32,
+    pub grid_height: i32,
+    pub wall_mode: WallMode,
+    pub difficulty: Difficulty,
+    pub stage: Option<usize>,
+    pub food_chances: FoodChances,
+    pub powerup_chance: f64,
+    pub end_tick: u32,
+    pub entries: Vec<ReplayEntry>,
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    BadMagic,
+    UnsupportedVersion(u8),
+    Truncated,
+    InvalidGrid(i32, i32),
+    InvalidDirection(u8),
+    InvalidWallMode(u8),
+    InvalidDifficulty(u8),
+    InvalidStage(u8),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::Io(err) => write!(f, "fayl xatosi: {}", err),
+            ReplayError::BadMagic => write!(f, "bu takror fayli emas"),
+            ReplayError::UnsupportedVersion(version) => write!(f, "takror versiyasi qo'llab-quvvatlanmaydi: {}", version),
+            ReplayError::Truncated => write!(f, "takror fayli to'liq emas"),
+            ReplayError::InvalidGrid(width, height) => write!(f, "noto'g'ri maydon o'lchami: {}x{}", width, height),
+            ReplayError::InvalidDirection(value) => write!(f, "noto'g'ri yo'nalish: {}", value),
+            ReplayError::InvalidWallMode(value) => write!(f, "noto'g'ri rejim: {}", value),
+            ReplayError::InvalidDifficulty(value) => write!(f, "noto'g'ri qiyinlik: {}", value),
+            ReplayError::InvalidStage(value) => write!(f, "noto'g'ri bosqich: {}", value),
+        }
+    }
+}
+
+impl From<io::Error> for ReplayError {
+    fn from(err: io::Error) -> Self {
+        ReplayError::Io(err)
+    }
+}
+
+impl From<Truncated> for ReplayError {
+    fn from(_: Truncated) -> Self {
+        ReplayError::Truncated
+    }
+}
+
+impl Replay {
+    pub fn new(seed: u64, level: &Level, rules: Rules, difficulty: Difficulty, stage: Option<usize>) -> Self {
+        Self {
+            seed,
+            level: level.name.clone(),
+            grid_width: level.width,
+            grid_height: level.height,
+            wall_mode: rules.wall_mode,
+            difficulty,
+            stage,
+            food_chances: rules.food_chances,
+            powerup_chance: rules.powerup_chance,
+            end_tick: 0,
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn truncate(&mut self, tick: u32) {
+        self.entries.retain(|entry| entry.tick < tick);
+    }
+
+    pub fn record(&mut self, tick: u32, direction: Direction) {
+        self.entries.push(ReplayEntry { tick, direction });
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let level = &self.level.as_bytes()[..self.level.len().min(u8::MAX as usize)];
+
+        let mut bytes = Vec::with_capacity(61 + level.len() + self.entries.len() * ENTRY_SIZE);
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.extend_from_slice(&(self.grid_width as u16).to_le_bytes());
+        bytes.extend_from_slice(&(self.grid_height as u16).to_le_bytes());
+        bytes.push(codec::wall_mode_to_byte(self.wall_mode));
+        bytes.push(codec::difficulty_to_byte(self.difficulty));
+        bytes.push(self.stage.map_or(NO_STAGE, |stage| stage as u8));
+        for chance in [self.food_chances.bonus, self.food_chances.shrink, self.food_chances.slow] {
+            bytes.extend_from_slice(&chance.to_le_bytes());
+        }
+        bytes.extend_from_slice(&self.powerup_chance.to_le_bytes());
+        bytes.extend_from_slice(&self.end_tick.to_le_bytes());
+        bytes.push(level.len() as u8);
+        bytes.extend_from_slice(level);
+        bytes.extend_from_slice(&(self.entries.len() as u32).to_le_bytes());
+
+        for entry in &self.entries {
+            bytes.extend_from_slice(&entry.tick.to_le_bytes());
+            bytes.push(codec::direction_to_byte(entry.direction));
+        }
+
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ReplayError> {
+        let mut reader = ByteReader::new(bytes);
+
+        if reader.take(4)? != MAGIC {
+            return Err(ReplayError::BadMagic);
+        }
+
+        let version = reader.u8()?;
+        if version != VERSION {
+            return Err(ReplayError::UnsupportedVersion(version));
+        }
+
+        let seed = reader.u64()?;
+        let grid_width = reader.u16()? as i32;
+        let grid_height = reader.u16()? as i32;
+        if !GRID_SIZE.contains(&grid_width) || !GRID_SIZE.contains(&grid_height) {
+            return Err(ReplayError::InvalidGrid(grid_width, grid_height));
+        }
+        let value = reader.u8()?;
+        let wall_mode = codec::wall_mode_from_byte(value).ok_or(ReplayError::InvalidWallMode(value))?;
+        let value = reader.u8()?;
+        let difficulty = codec::difficulty_from_byte(value).ok_or(ReplayError::InvalidDifficulty(value))?;
+        let stage = match reader.u8()? {
+            NO_STAGE => None,
+            value if (value as usize) < STAGES.len() => Some(value as usize),
+            value => return Err(ReplayError::InvalidStage(value)),
+        };
+        let food_chances = FoodChances {
+            bonus: reader.f64()?,
+            shrink: reader.f64()?,
+            slow: reader.f64()?,
+        };
+        let powerup_chance = reader.f64()?;
+        let end_tick = reader.u32()?;
+        let len = reader.u8()? as usize;
+        let level = String::from_utf8_lossy(reader.take(len)?).into_owned();
+
+        let count = reader.u32()? as usize;
+        if reader.remaining().len() != count * ENTRY_SIZE {
+            return Err(ReplayError::Truncated);
+        }
+
+        let mut entries = Vec::with_capacity(count);
+        for chunk in reader.remaining().chunks_exact(ENTRY_SIZE) {
+            let tick = u32::from_le_bytes(chunk[0..4].try_into().unwrap());
+            let direction = codec::direction_from_byte(chunk[4]).ok_or(ReplayError::InvalidDirection(chunk[4]))?;
+            entries.push(ReplayEntry { tick, direction });
+        }
+
+        Ok(Self {
+            seed,
+            level,
+            grid_width,
+            grid_height,
+            wall_mode,
+            difficulty,
+            stage,
+            food_chances,
+            powerup_chance,
+            end_tick,
+            entries,
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), ReplayError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, self.to_bytes())?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self, ReplayError> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+}
+
+pub struct ReplayPlayer {
+    replay: Replay,
+    cursor: usize,
+}
+
+impl ReplayPlayer {
+    pub fn new(replay: Replay) -> Self {
+        Self { replay, cursor: 0 }
+    }
+
+    pub fn input_for(&mut self, tick: u32) -> Option<Direction> {
+        let mut input = None;
+        while let Some(entry) = self.replay.entries.get(self.cursor) {
+            if entry.tick > tick {
+                break;
+            }
+            if entry.tick == tick {
+                input = Some(entry.direction);
+            }
+            self.cursor += 1;
+        }
+        input
+    }
+
+    pub fn finished(&self, tick: u32) -> bool {
+        tick >= self.replay.end_tick
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::GameConfig;
+    use crate::level::Level;
+    use crate::simulation::Simulation;
+
+    const TICKS: u32 = 300;
+
+    fn simulation(seed: u64) -> Simulation {
+        let rules = GameConfig::default().rules(WallMode::Wrap);
+        Simulation::new(&Level::open(25, 20), rules, seed)
+    }
+
+    fn replay(seed: u64) -> Replay {
+        let rules = GameConfig::default().rules(WallMode::Wrap);
+        Replay::new(seed, &Level::open(25, 20), rules, Difficulty::Hard, Some(2))
+    }
+
+    fn scripted_input(tick: u32) -> Option<Direction> {
+        const TURNS: [Direction; 4] = [Direction::Down, Direction::Left, Direction::Up, Direction::Right];
+        tick.is_multiple_of(4).then(|| TURNS[(tick / 4) as usize % TURNS.len()])
+    }
+
+    fn record(seed: u64) -> (Replay, Simulation) {
+        let mut simulation = simulation(seed);
+        let mut replay = replay(seed);
+        for tick in 0..TICKS {
+            let input = scripted_input(tick);
+            if let Some(direction) = input {
+                replay.record(tick, direction);
+            }
+            simulation.step(&[input]);
+        }
+        replay.end_tick = TICKS;
+        (replay, simulation)
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        let (replay, _) = record(9);
+        let decoded = Replay::from_bytes(&replay.to_bytes()).expect("replay decodes");
+
+        assert_eq!(decoded.seed, replay.seed);
+        assert_eq!(decoded.level, replay.level);
+        assert_eq!((decoded.grid_width, decoded.grid_height), (replay.grid_width, replay.grid_height));
+        assert_eq!(decoded.wall_mode, replay.wall_mode);
+        assert_eq!(decoded.difficulty, replay.difficulty);
+        assert_eq!(decoded.stage, replay.stage);
+        assert_eq!(decoded.end_tick, replay.end_tick);
+        assert_eq!(decoded.food_chances, replay.food_chances);
+        assert_eq!(decoded.entries, replay.entries);
+    }
+
+    #[test]
+    fn playback_reproduces_the_game() {
+        let (replay, original) = record(9);
+        let decoded = Replay::from_bytes(&replay.to_bytes()).expect("replay decodes");
+
+        let mut simulation = simulation(decoded.seed);
+        let mut player = ReplayPlayer::new(decoded);
+        let mut tick = 0;
+        while !player.finished(tick) {
+            simulation.step(&[player.input_for(tick)]);
+            tick += 1;
+        }
+
+        assert_eq!(simulation.player().body, original.player().body);
+        assert_eq!(simulation.foods(), original.foods());
+        assert_eq!(simulation.player().score, original.player().score);
+    }
+
+    #[test]
+    fn rejects_damaged_files() {
+        let bytes = replay(1).to_bytes();
+        let mut wrong_magic = bytes.clone();
+        wrong_magic[0] = b'X';
+
+        assert!(matches!(Replay::from_bytes(&wrong_magic), Err(ReplayError::BadMagic)));
+        assert!(matches!(Replay::from_bytes(&bytes[..bytes.len() - 1]), Err(ReplayError::Truncated)));
+    }
+
+    #[test]
+    fn rejects_out_of_range_grid() {
+        for (width, height) in [(4, 20), (25, 0), (201, 20), (25, 65535)] {
+            let mut replay = replay(1);
+            replay.grid_width = width;
+            replay.grid_height = height;
+            assert!(matches!(
+                Replay::from_bytes(&replay.to_bytes()),
+                Err(ReplayError::InvalidGrid(w, h)) if (w, h) == (width, height)
+            ));
+        }
+    }
+}
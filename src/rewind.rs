@@ -0,0 +1,43 @@
+use std::collections::VecDeque;
+use crate::simulation::Simulation;
+
+pub const REWIND_CAPACITY: usize = 100;
+pub const REWIND_INTERVAL: f64 = 0.05;
+
+pub struct Snapshot {
+    pub simulation: Simulation,
+    pub tick: u32,
+}
+
+pub struct RewindBuffer {
+    snapshots: VecDeque<Snapshot>,
+    capacity: usize,
+}
+
+impl RewindBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            snapshots: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub fn push(&mut self, simulation: &Simulation, tick: u32) {
+        if self.snapshots.len() >= self.capacity {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back(Snapshot { simulation: simulation.clone(), tick });
+    }
+
+    pub fn pop(&mut self) -> Option<Snapshot> {
+        self.snapshots.pop_back()
+    }
+
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+}
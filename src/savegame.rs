@@ -0,0 +1,235 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use crate::codec::{self, ByteReader, Truncated};
+use crate::difficulty::Difficulty;
+use crate::direction::Direction;
+use crate::replay::Replay;
+use crate::simulation::Simulation;
+use crate::storage;
+
+const MAGIC: &[u8; 4] = b"ILSV";
+const VERSION: u8 = 2;
+const NO_STAGE: u8 = u8::MAX;
+
+pub struct SaveGame {
+    pub simulation: Simulation,
+    pub difficulty: Difficulty,
+    pub stage: Option<usize>,
+    pub autopilot: bool,
+    pub autopilot_used: bool,
+    pub practice: bool,
+    pub tick: u32,
+    pub run_time: f64,
+    pub buffered: Vec<Direction>,
+    pub recording: Replay,
+}
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(io::Error),
+    BadMagic,
+    UnsupportedVersion(u8),
+    Truncated,
+    Invalid(&'static str),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SaveError::Io(err) => write!(f, "fayl xatosi: {}", err),
+            SaveError::BadMagic => write!(f, "bu saqlangan o'yin fayli emas"),
+            SaveError::UnsupportedVersion(version) => {
+                write!(f, "saqlangan o'yin boshqa versiyadan ({}), davom ettirib bo'lmaydi", version)
+            }
+            SaveError::Truncated => write!(f, "saqlangan o'yin fayli to'liq emas"),
+            SaveError::Invalid(what) => write!(f, "saqlangan o'yinda noto'g'ri qiymat: {}", what),
+        }
+    }
+}
+
+impl From<io::Error> for SaveError {
+    fn from(err: io::Error) -> Self {
+        SaveError::Io(err)
+    }
+}
+
+impl From<Truncated> for SaveError {
+    fn from(_: Truncated) -> Self {
+        SaveError::Truncated
+    }
+}
+
+impl SaveGame {
+    pub fn path() -> PathBuf {
+        storage::data_dir().join("save.ilsv")
+    }
+
+    pub fn exists() -> bool {
+        Self::path().exists()
+    }
+
+    pub fn delete() -> io::Result<()> {
+        match fs::remove_file(Self::path()) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+
+        self.simulation.encode(&mut bytes);
+        bytes.push(codec::difficulty_to_byte(self.difficulty));
+        bytes.push(self.stage.map_or(NO_STAGE, |stage| stage as u8));
+        bytes.push(self.autopilot as u8);
+        bytes.push(self.autopilot_used as u8);
+        bytes.push(self.practice as u8);
+        bytes.extend_from_slice(&self.tick.to_le_bytes());
+        bytes.extend_from_slice(&self.run_time.to_le_bytes());
+        bytes.push(self.buffered.len() as u8);
+        bytes.extend(self.buffered.iter().map(|&direction| codec::direction_to_byte(direction)));
+
+        let recording = self.recording.to_bytes();
+        bytes.extend_from_slice(&(recording.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&recording);
+
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SaveError> {
+        let mut reader = ByteReader::new(bytes);
+
+        if reader.take(4)? != MAGIC {
+            return Err(SaveError::BadMagic);
+        }
+        let version = reader.u8()?;
+        if version != VERSION {
+            return Err(SaveError::UnsupportedVersion(version));
+        }
+
+        let simulation = Simulation::decode(&mut reader)?;
+        let difficulty = codec::difficulty_from_byte(reader.u8()?).ok_or(SaveError::Invalid("qiyinlik"))?;
+        let stage = match reader.u8()? {
+            NO_STAGE => None,
+            stage => Some(stage as usize),
+        };
+        let autopilot = reader.u8()? != 0;
+        let autopilot_used = reader.u8()? != 0;
+        let practice = reader.u8()? != 0;
+        let tick = reader.u32()?;
+        let run_time = reader.f64()?;
+
+        let count = reader.u8()? as usize;
+        let mut buffered = Vec::with_capacity(count);
+        for _ in 0..count {
+            buffered.push(codec::direction_from_byte(reader.u8()?).ok_or(SaveError::Invalid("yo'nalish"))?);
+        }
+
+        let len = reader.u32()? as usize;
+        let recording = Replay::from_bytes(reader.take(len)?).map_err(|_| SaveError::Invalid("takror"))?;
+        if !reader.remaining().is_empty() {
+            return Err(SaveError::Invalid("fayl oxiri"));
+        }
+
+        Ok(Self {
+            simulation,
+            difficulty,
+            stage,
+            autopilot,
+            autopilot_used,
+            practice,
+            tick,
+            run_time,
+            buffered,
+            recording,
+        })
+    }
+
+    pub fn save(&self) -> Result<(), SaveError> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, self.to_bytes())?;
+        Ok(())
+    }
+
+    pub fn load() -> Result<Self, SaveError> {
+        Self::from_bytes(&fs::read(Self::path())?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::autopilot::Autopilot;
+    use crate::config::GameConfig;
+    use crate::level::Level;
+    use crate::wall_mode::WallMode;
+
+    fn save_after(ticks: u32) -> SaveGame {
+        let level = Level::open(12, 10);
+        let rules = Difficulty::Hard.rules(&GameConfig::default(), WallMode::Wrap);
+        let mut simulation = Simulation::new(&level, rules, 5);
+        let mut recording = Replay::new(5, &level, rules, Difficulty::Hard, None);
+        let autopilot = Autopilot::new(&simulation);
+        for _ in 0..ticks {
+            let input = autopilot.choose(&simulation, 0);
+            if let Some(direction) = input {
+                recording.record(simulation.tick(), direction);
+            }
+            simulation.step(&[input]);
+        }
+
+        SaveGame {
+            simulation,
+            difficulty: Difficulty::Hard,
+            stage: Some(1),
+            autopilot: true,
+            autopilot_used: true,
+            practice: false,
+            tick: ticks,
+            run_time: 12.5,
+            buffered: vec![Direction::Up, Direction::Left],
+            recording,
+        }
+    }
+
+    #[test]
+    fn round_trip_is_exact() {
+        let save = save_after(150);
+        let bytes = save.to_bytes();
+        let loaded = SaveGame::from_bytes(&bytes).expect("save decodes");
+
+        assert_eq!(loaded.to_bytes(), bytes);
+        assert_eq!(loaded.stage, save.stage);
+        assert_eq!(loaded.buffered, save.buffered);
+        assert_eq!(loaded.run_time, save.run_time);
+    }
+
+    #[test]
+    fn loaded_game_plays_on_identically() {
+        let mut original = save_after(150).simulation;
+        let mut loaded = SaveGame::from_bytes(&save_after(150).to_bytes()).expect("save decodes").simulation;
+        let autopilot = Autopilot::new(&original);
+
+        for _ in 0..300 {
+            let input = autopilot.choose(&original, 0);
+            assert_eq!(original.step(&[input]), loaded.step(&[input]));
+        }
+        assert_eq!(original.player().body, loaded.player().body);
+        assert_eq!(original.foods(), loaded.foods());
+        assert_eq!(original.powerups(), loaded.powerups());
+    }
+
+    #[test]
+    fn rejects_out_of_range_grid() {
+        let mut bytes = save_after(0).to_bytes();
+        bytes[5..9].copy_from_slice(&300i32.to_le_bytes());
+        assert!(matches!(SaveGame::from_bytes(&bytes), Err(SaveError::Invalid(_))));
+    }
+}
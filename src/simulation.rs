@@ -0,0 +1,838 @@
+use crate::position::Position;
+use crate::direction::Direction;
+use crate::spatial_hash::SpatialHash;
+use crate::wall_mode::WallMode;
+use crate::level::Level;
+use crate::food::{Food, FoodChances, FoodKind, BONUS_MULTIPLIER, SHRINK_AMOUNT, SLOW_FACTOR, SLOW_TICKS};
+use crate::powerup::{ActiveEffect, PowerUp, PowerUpKind, MAGNET_RADIUS, POWERUP_LIFETIME, SCORE_MULTIPLIER};
+use crate::codec::{self, ByteReader};
+use crate::savegame::SaveError;
+use crate::config::GRID_SIZE;
+
+pub const VICTORY_BONUS: i32 = 1000;
+const SPAWN_CLEARANCE: i32 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SimEvent {
+    AteFood(usize, Food),
+    Grew(usize),
+    Shrank(usize, usize),
+    FoodExpired(Food),
+    PickedPowerUp(usize, PowerUpKind),
+    GhostUsed(usize),
+    Died(usize),
+    Won,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Rules {
+    pub wall_mode: WallMode,
+    pub move_interval: f64,
+    pub speed_multiplier: f64,
+    pub min_move_interval: f64,
+    pub food_score: i32,
+    pub food_chances: FoodChances,
+    pub powerup_chance: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Snake {
+    pub body: Vec<Position>,
+    pub direction: Direction,
+    pub score: i32,
+    pub alive: bool,
+    pub effects: Vec<ActiveEffect>,
+}
+
+impl Snake {
+    fn new(spawn: Position, direction: Direction, capacity: usize) -> Self {
+        let mut body = Vec::with_capacity(capacity);
+        body.push(spawn);
+        Self {
+            body,
+            direction,
+            score: 0,
+            alive: true,
+            effects: Vec::new(),
+        }
+    }
+
+    pub fn head(&self) -> Position {
+        self.body[self.body.len() - 1]
+    }
+
+    pub fn has_effect(&self, kind: PowerUpKind) -> bool {
+        self.effects.iter().any(|effect| effect.kind == kind)
+    }
+}
+
+#[derive(Clone)]
+pub struct Simulation {
+    snakes: Vec<Snake>,
+    snake_count: usize,
+    foods: Vec<Food>,
+    powerups: Vec<PowerUp>,
+    grid_width: i32,
+    grid_height: i32,
+    walls: Vec<Position>,
+    spawn: Position,
+    rules: Rules,
+    move_interval: f64,
+    slow_ticks: u32,
+    tick: u32,
+    won: bool,
+    seed: u64,
+
+    rng: fastrand::Rng,
+    spatial_hash: SpatialHash,
+}
+
+impl Simulation {
+    pub fn new(level: &Level, rules: Rules, seed: u64) -> Self {
+        let mut simulation = Simulation {
+            snakes: Vec::new(),
+            snake_count: 1,
+            foods: Vec::new(),
+            powerups: Vec::new(),
+            grid_width: level.width,
+            grid_height: level.height,
+            walls: level.walls.clone(),
+            spawn: level.spawn,
+            rules,
+            move_interval: rules.move_interval,
+            slow_ticks: 0,
+            tick: 0,
+            won: false,
+            seed,
+
+            rng: fastrand::Rng::with_seed(seed),
+            spatial_hash: SpatialHash::new(1),
+        };
+
+        simulation.reset(seed);
+        simulation
+    }
+
+    pub fn reset(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng.seed(seed);
+
+        let capacity = (self.grid_width * self.grid_height) as usize;
+        self.snakes = self
+            .spawn_points(self.snake_count)
+            .into_iter()
+            .map(|(spawn, direction)| Snake::new(spawn, direction, capacity))
+            .collect();
+
+        self.move_interval = self.rules.move_interval;
+        self.slow_ticks = 0;
+        self.tick = 0;
+        self.won = false;
+        self.foods.clear();
+        self.powerups.clear();
+        self.update_spatial_hash();
+        self.spawn_food(FoodKind::Normal);
+    }
+
+    pub fn load_level(&mut self, level: &Level) {
+        self.grid_width = level.width;
+        self.grid_height = level.height;
+        self.walls = level.walls.clone();
+        self.spawn = level.spawn;
+    }
+
+    pub fn set_snake_count(&mut self, count: usize) {
+        self.snake_count = count.max(1);
+    }
+
+    pub fn snakes(&self) -> &[Snake] {
+        &self.snakes
+    }
+
+    pub fn player(&self) -> &Snake {
+        &self.snakes[0]
+    }
+
+    pub fn foods(&self) -> &[Food] {
+        &self.foods
+    }
+
+    pub fn powerups(&self) -> &[PowerUp] {
+        &self.powerups
+    }
+
+    pub fn tick(&self) -> u32 {
+        self.tick
+    }
+
+    pub fn slow_ticks(&self) -> u32 {
+        self.slow_ticks
+    }
+
+    pub fn walls(&self) -> &[Position] {
+        &self.walls
+    }
+
+    pub fn grid_width(&self) -> i32 {
+        self.grid_width
+    }
+
+    pub fn grid_height(&self) -> i32 {
+        self.grid_height
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn wall_mode(&self) -> WallMode {
+        self.rules.wall_mode
+    }
+
+    pub fn set_rules(&mut self, rules: Rules) {
+        self.rules = rules;
+    }
+
+    pub fn move_interval(&self) -> f64 {
+        if self.slow_ticks > 0 {
+            self.move_interval * SLOW_FACTOR
+        } else {
+            self.move_interval
+        }
+    }
+
+    pub fn step(&mut self, inputs: &[Option<Direction>]) -> Vec<SimEvent> {
+        let mut events = Vec::new();
+
+        if self.won || !self.snakes.iter().any(|snake| snake.alive) {
+            return events;
+        }
+
+        self.tick += 1;
+        self.slow_ticks = self.slow_ticks.saturating_sub(1);
+        for snake in &mut self.snakes {
+            for effect in &mut snake.effects {
+                effect.remaining -= 1;
+            }
+            snake.effects.retain(|effect| effect.remaining > 0);
+        }
+
+        let mut heads = Vec::with_capacity(self.snakes.len());
+        for (index, snake) in self.snakes.iter_mut().enumerate() {
+            if !snake.alive {
+                heads.push(None);
+                continue;
+            }
+
+            if let Some(direction) = inputs.get(index).copied().flatten()
+                && !snake.direction.is_opposite(direction) {
+                snake.direction = direction;
+            }
+            heads.push(Some(snake.head().moved(snake.direction)));
+        }
+        if self.rules.wall_mode == WallMode::Wrap {
+            for head in heads.iter_mut().flatten() {
+                *head = self.wrap(*head);
+            }
+        }
+
+        let mut crashed = Vec::new();
+        for (index, head) in heads.iter().enumerate() {
+            let Some(head) = *head else { continue };
+
+            let head_on = heads
+                .iter()
+                .enumerate()
+                .any(|(other, other_head)| other != index && *other_head == Some(head));
+            let blocked = !self.in_bounds(head) || self.spatial_hash.contains(head);
+
+            if head_on || (blocked && !self.try_ghost(index, head, &mut events)) {
+                crashed.push(index);
+            }
+        }
+        for &index in &crashed {
+            self.snakes[index].alive = false;
+            heads[index] = None;
+            events.push(SimEvent::Died(index));
+        }
+        if !self.snakes.iter().any(|snake| snake.alive) {
+            return events;
+        }
+
+        for (index, head) in heads.iter().enumerate() {
+            let Some(head) = *head else { continue };
+            self.snakes[index].body.push(head);
+
+            if let Some(position) = self.powerups.iter().position(|powerup| powerup.position == head) {
+                let powerup = self.powerups.remove(position);
+                self.activate(index, powerup.kind);
+                events.push(SimEvent::PickedPowerUp(index, powerup.kind));
+            }
+
+            match self.foods.iter().position(|food| food.position == head) {
+                Some(position) => {
+                    let food = self.foods.remove(position);
+                    events.push(SimEvent::AteFood(index, food));
+                    self.eat(index, food, &mut events);
+                }
+                None => {
+                    self.snakes[index].body.remove(0);
+                }
+            }
+        }
+
+        self.update_spatial_hash();
+
+        if self.spatial_hash.occupied() == (self.grid_width * self.grid_height) as usize {
+            self.won = true;
+            self.foods.clear();
+            self.powerups.clear();
+            for snake in self.snakes.iter_mut().filter(|snake| snake.alive) {
+                snake.score += VICTORY_BONUS;
+            }
+            events.push(SimEvent::Won);
+            return events;
+        }
+
+        self.expire_foods(&mut events);
+        let tick = self.tick;
+        self.powerups.retain(|powerup| tick < powerup.expires_at);
+        for index in 0..self.snakes.len() {
+            let snake = &self.snakes[index];
+            if snake.alive && snake.has_effect(PowerUpKind::Magnet) {
+                self.pull_foods(snake.head());
+            }
+        }
+        if !self.foods.iter().any(|food| food.kind == FoodKind::Normal) {
+            self.spawn_food(FoodKind::Normal);
+        }
+
+        events
+    }
+
+    pub fn encode(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.grid_width.to_le_bytes());
+        bytes.extend_from_slice(&self.grid_height.to_le_bytes());
+        bytes.extend_from_slice(&(self.walls.len() as u32).to_le_bytes());
+        for &wall in &self.walls {
+            codec::put_position(bytes, wall);
+        }
+        codec::put_position(bytes, self.spawn);
+
+        bytes.push(codec::wall_mode_to_byte(self.rules.wall_mode));
+        for value in [self.rules.move_interval, self.rules.speed_multiplier, self.rules.min_move_interval] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes.extend_from_slice(&self.rules.food_score.to_le_bytes());
+        let chances = self.rules.food_chances;
+        for value in [chances.bonus, chances.shrink, chances.slow, self.rules.powerup_chance] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+
+        bytes.push(self.snakes.len() as u8);
+        for snake in &self.snakes {
+            bytes.extend_from_slice(&(snake.body.len() as u32).to_le_bytes());
+            for &pos in &snake.body {
+                codec::put_position(bytes, pos);
+            }
+            bytes.push(codec::direction_to_byte(snake.direction));
+            bytes.extend_from_slice(&snake.score.to_le_bytes());
+            bytes.push(snake.alive as u8);
+            bytes.push(snake.effects.len() as u8);
+            for effect in &snake.effects {
+                bytes.push(codec::powerup_kind_to_byte(effect.kind));
+                bytes.extend_from_slice(&effect.remaining.to_le_bytes());
+            }
+        }
+
+        bytes.extend_from_slice(&(self.foods.len() as u32).to_le_bytes());
+        for food in &self.foods {
+            codec::put_position(bytes, food.position);
+            bytes.push(codec::food_kind_to_byte(food.kind));
+            bytes.extend_from_slice(&food.expires_at.unwrap_or(u32::MAX).to_le_bytes());
+        }
+        bytes.extend_from_slice(&(self.powerups.len() as u32).to_le_bytes());
+        for powerup in &self.powerups {
+            codec::put_position(bytes, powerup.position);
+            bytes.push(codec::powerup_kind_to_byte(powerup.kind));
+            bytes.extend_from_slice(&powerup.expires_at.to_le_bytes());
+        }
+
+        bytes.extend_from_slice(&self.move_interval.to_le_bytes());
+        bytes.extend_from_slice(&self.slow_ticks.to_le_bytes());
+        bytes.extend_from_slice(&self.tick.to_le_bytes());
+        bytes.push(self.won as u8);
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.extend_from_slice(&self.rng.get_seed().to_le_bytes());
+    }
+
+    pub fn decode(reader: &mut ByteReader) -> Result<Self, SaveError> {
+        let grid_width = reader.i32()?;
+        let grid_height = reader.i32()?;
+        if !GRID_SIZE.contains(&grid_width) || !GRID_SIZE.contains(&grid_height) {
+            return Err(SaveError::Invalid("maydon o'lchami"));
+        }
+        let wall_count = reader.u32()? as usize;
+        let mut walls = Vec::new();
+        for _ in 0..wall_count {
+            walls.push(reader.position()?);
+        }
+        let spawn = reader.position()?;
+
+        let wall_mode = codec::wall_mode_from_byte(reader.u8()?).ok_or(SaveError::Invalid("rejim"))?;
+        let rules = Rules {
+            wall_mode,
+            move_interval: reader.f64()?,
+            speed_multiplier: reader.f64()?,
+            min_move_interval: reader.f64()?,
+            food_score: reader.i32()?,
+            food_chances: FoodChances {
+                bonus: reader.f64()?,
+                shrink: reader.f64()?,
+                slow: reader.f64()?,
+            },
+            powerup_chance: reader.f64()?,
+        };
+        if !Self::valid_interval(rules.move_interval) || !Self::valid_interval(rules.min_move_interval) {
+            return Err(SaveError::Invalid("tezlik"));
+        }
+
+        let snake_count = reader.u8()? as usize;
+        if snake_count != 1 {
+            return Err(SaveError::Invalid("ilonlar soni"));
+        }
+        let mut snakes = Vec::with_capacity(snake_count);
+        for _ in 0..snake_count {
+            let body_len = reader.u32()? as usize;
+            if body_len == 0 {
+                return Err(SaveError::Invalid("ilon uzunligi"));
+            }
+            let mut body = Vec::new();
+            for _ in 0..body_len {
+                body.push(reader.position()?);
+            }
+            let direction = codec::direction_from_byte(reader.u8()?).ok_or(SaveError::Invalid("yo'nalish"))?;
+            let score = reader.i32()?;
+            let alive = reader.u8()? != 0;
+            let effect_count = reader.u8()? as usize;
+            let mut effects = Vec::with_capacity(effect_count);
+            for _ in 0..effect_count {
+                let kind = codec::powerup_kind_from_byte(reader.u8()?).ok_or(SaveError::Invalid("kuchaytirgich"))?;
+                let remaining = reader.u32()?;
+                if remaining == 0 {
+                    return Err(SaveError::Invalid("kuchaytirgich muddati"));
+                }
+                effects.push(ActiveEffect { kind, remaining });
+            }
+            snakes.push(Snake { body, direction, score, alive, effects });
+        }
+
+        let food_count = reader.u32()? as usize;
+        let mut foods = Vec::new();
+        for _ in 0..food_count {
+            let position = reader.position()?;
+            let kind = codec::food_kind_from_byte(reader.u8()?).ok_or(SaveError::Invalid("ovqat"))?;
+            let expires_at = Some(reader.u32()?).filter(|&tick| tick != u32::MAX);
+            foods.push(Food { position, kind, expires_at });
+        }
+        let powerup_count = reader.u32()? as usize;
+        let mut powerups = Vec::new();
+        for _ in 0..powerup_count {
+            let position = reader.position()?;
+            let kind = codec::powerup_kind_from_byte(reader.u8()?).ok_or(SaveError::Invalid("kuchaytirgich"))?;
+            powerups.push(PowerUp { position, kind, expires_at: reader.u32()? });
+        }
+
+        let mut simulation = Simulation {
+            snake_count: snakes.len(),
+            snakes,
+            foods,
+            powerups,
+            grid_width,
+            grid_height,
+            walls,
+            spawn,
+            rules,
+            move_interval: reader.f64()?,
+            slow_ticks: reader.u32()?,
+            tick: reader.u32()?,
+            won: reader.u8()? != 0,
+            seed: reader.u64()?,
+
+            rng: fastrand::Rng::with_seed(reader.u64()?),
+            spatial_hash: SpatialHash::new(1),
+        };
+        let mut positions = simulation.snakes
+            .iter()
+            .flat_map(|snake| snake.body.iter().copied())
+            .chain(simulation.foods.iter().map(|food| food.position))
+            .chain(simulation.powerups.iter().map(|powerup| powerup.position))
+            .chain(simulation.walls.iter().copied());
+        if !positions.all(|position| simulation.in_bounds(position)) {
+            return Err(SaveError::Invalid("koordinata"));
+        }
+        if !Self::valid_interval(simulation.move_interval) {
+            return Err(SaveError::Invalid("tezlik"));
+        }
+        simulation.update_spatial_hash();
+
+        Ok(simulation)
+    }
+
+    fn valid_interval(interval: f64) -> bool {
+        interval.is_finite() && interval > 0.0
+    }
+
+    fn try_ghost(&mut self, index: usize, head: Position, events: &mut Vec<SimEvent>) -> bool {
+        let in_bounds = self.in_bounds(head);
+        let snake = &mut self.snakes[index];
+        if !in_bounds || !snake.body.contains(&head) || !snake.has_effect(PowerUpKind::Ghost) {
+            return false;
+        }
+
+        snake.effects.retain(|effect| effect.kind != PowerUpKind::Ghost);
+        events.push(SimEvent::GhostUsed(index));
+        true
+    }
+
+    fn eat(&mut self, index: usize, food: Food, events: &mut Vec<SimEvent>) {
+        match food.kind {
+            FoodKind::Normal => {
+                self.add_score(index, self.rules.food_score);
+                events.push(SimEvent::Grew(index));
+
+                if self.move_interval > self.rules.min_move_interval {
+                    self.move_interval *= self.rules.speed_multiplier;
+                }
+
+                if self.rules.food_chances.total() > 0.0
+                    && let Some(kind) = self.rules.food_chances.pick(self.rng.f64())
+                    && !self.foods.iter().any(|food| food.kind != FoodKind::Normal) {
+                    self.update_spatial_hash();
+                    self.spawn_food(kind);
+                }
+
+                if self.rules.powerup_chance > 0.0
+                    && self.rng.f64() < self.rules.powerup_chance
+                    && self.powerups.is_empty() {
+                    let kind = PowerUpKind::ALL[self.rng.usize(..PowerUpKind::ALL.len())];
+                    self.update_spatial_hash();
+                    self.spawn_powerup(kind);
+                }
+            }
+            FoodKind::Bonus => {
+                self.add_score(index, self.rules.food_score * BONUS_MULTIPLIER);
+                events.push(SimEvent::Grew(index));
+            }
+            FoodKind::Shrink => {
+                self.add_score(index, self.rules.food_score);
+                let body = &mut self.snakes[index].body;
+                let removed = (body.len() - 1).min(SHRINK_AMOUNT + 1);
+                body.drain(..removed);
+                events.push(SimEvent::Shrank(index, removed - 1));
+            }
+            FoodKind::Slow => {
+                self.add_score(index, self.rules.food_score);
+                self.snakes[index].body.remove(0);
+                self.slow_ticks = SLOW_TICKS;
+            }
+        }
+    }
+
+    fn add_score(&mut self, index: usize, points: i32) {
+        let snake = &mut self.snakes[index];
+        if snake.has_effect(PowerUpKind::Multiplier) {
+            snake.score += points * SCORE_MULTIPLIER;
+        } else {
+            snake.score += points;
+        }
+    }
+
+    fn activate(&mut self, index: usize, kind: PowerUpKind) {
+        let remaining = kind.duration();
+        let effects = &mut self.snakes[index].effects;
+        match effects.iter_mut().find(|effect| effect.kind == kind) {
+            Some(effect) => effect.remaining = remaining,
+            None => effects.push(ActiveEffect { kind, remaining }),
+        }
+    }
+
+    fn pull_foods(&mut self, head: Position) {
+        for index in 0..self.foods.len() {
+            let food = self.foods[index].position;
+            let dx = head.x - food.x;
+            let dy = head.y - food.y;
+            if dx.abs() + dy.abs() > MAGNET_RADIUS {
+                continue;
+            }
+
+            let target = if dx.abs() >= dy.abs() {
+                Position { x: food.x + dx.signum(), y: food.y }
+            } else {
+                Position { x: food.x, y: food.y + dy.signum() }
+            };
+            if self.is_free(target) {
+                self.foods[index].position = target;
+            }
+        }
+    }
+
+    fn is_free(&self, pos: Position) -> bool {
+        !self.spatial_hash.contains(pos)
+            && !self.foods.iter().any(|food| food.position == pos)
+            && !self.powerups.iter().any(|powerup| powerup.position == pos)
+    }
+
+    fn expire_foods(&mut self, events: &mut Vec<SimEvent>) {
+        let tick = self.tick;
+        self.foods.retain(|food| {
+            let expired = food.expires_at.is_some_and(|expires_at| tick >= expires_at);
+            if expired {
+                events.push(SimEvent::FoodExpired(*food));
+            }
+            !expired
+        });
+    }
+
+    fn spawn_points(&self, count: usize) -> Vec<(Position, Direction)> {
+        let mut points = vec![(self.spawn, Direction::Right)];
+        let facing = |pos: Position| if pos.x < self.grid_width / 2 { Direction::Right } else { Direction::Left };
+
+        while points.len() < count {
+            let clear = |pos: Position| {
+                let direction = facing(pos);
+                (0..=SPAWN_CLEARANCE).all(|distance| {
+                    let mut cell = pos;
+                    for _ in 0..distance {
+                        cell = cell.moved(direction);
+                    }
+                    self.in_bounds(cell)
+                        && !self.walls.contains(&cell)
+                        && !points.iter().any(|(point, _)| *point == cell)
+                })
+            };
+            let distance = |pos: Position| {
+                points
+                    .iter()
+                    .map(|(point, _)| (point.x - pos.x).abs() + (point.y - pos.y).abs())
+                    .min()
+                    .unwrap_or(0)
+            };
+
+            let mut best: Option<Position> = None;
+            for y in 1..self.grid_height - 1 {
+                for x in 1..self.grid_width - 1 {
+                    let pos = Position { x, y };
+                    if clear(pos) && best.is_none_or(|best| distance(pos) > distance(best)) {
+                        best = Some(pos);
+                    }
+                }
+            }
+
+            let Some(pos) = best else { break };
+            points.push((pos, facing(pos)));
+        }
+
+        points
+    }
+
+    fn wrap(&self, pos: Position) -> Position {
+        Position {
+            x: pos.x.rem_euclid(self.grid_width),
+            y: pos.y.rem_euclid(self.grid_height),
+        }
+    }
+
+    fn in_bounds(&self, pos: Position) -> bool {
+        pos.x >= 0 && pos.x < self.grid_width && pos.y >= 0 && pos.y < self.grid_height
+    }
+
+    fn free_cells(&self) -> Vec<Position> {
+        let capacity = (self.grid_width * self.grid_height) as usize - self.spatial_hash.occupied();
+        let mut cells = Vec::with_capacity(capacity);
+        for y in 0..self.grid_height {
+            for x in 0..self.grid_width {
+                let pos = Position { x, y };
+                if self.is_free(pos) {
+                    cells.push(pos);
+                }
+            }
+        }
+        cells
+    }
+
+    fn spawn_food(&mut self, kind: FoodKind) {
+        let cells = self.free_cells();
+        if cells.is_empty() {
+            return;
+        }
+
+        self.foods.push(Food {
+            position: cells[self.rng.usize(..cells.len())],
+            kind,
+            expires_at: kind.lifetime().map(|lifetime| self.tick + lifetime),
+        });
+    }
+
+    fn spawn_powerup(&mut self, kind: PowerUpKind) {
+        let cells = self.free_cells();
+        if cells.is_empty() {
+            return;
+        }
+
+        self.powerups.push(PowerUp {
+            position: cells[self.rng.usize(..cells.len())],
+            kind,
+            expires_at: self.tick + POWERUP_LIFETIME,
+        });
+    }
+
+    fn update_spatial_hash(&mut self) {
+        self.spatial_hash.clear();
+        for &pos in &self.walls {
+            self.spatial_hash.insert(pos);
+        }
+        for snake in self.snakes.iter().filter(|snake| snake.alive) {
+            for &pos in &snake.body {
+                self.spatial_hash.insert(pos);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::GameConfig;
+
+    fn board(width: i32, height: i32, seed: u64) -> Simulation {
+        let rules = GameConfig::default().rules(WallMode::Solid);
+        Simulation::new(&Level::open(width, height), rules, seed)
+    }
+
+    fn simulation(seed: u64) -> Simulation {
+        board(25, 20, seed)
+    }
+
+    fn step(simulation: &mut Simulation, input: Option<Direction>) {
+        simulation.step(&[input]);
+    }
+
+    fn next_food(simulation: &mut Simulation) -> Position {
+        simulation.foods.clear();
+        simulation.spawn_food(FoodKind::Normal);
+        simulation.foods().first().expect("board has free cells").position
+    }
+
+    fn fill_board_except(simulation: &mut Simulation, free: &[Position]) {
+        let (width, height) = (simulation.grid_width(), simulation.grid_height());
+        simulation.powerups.clear();
+        simulation.snakes[0].body = (0..height)
+            .flat_map(|y| (0..width).map(move |x| Position { x, y }))
+            .filter(|pos| !free.contains(pos))
+            .collect();
+        simulation.update_spatial_hash();
+    }
+
+    fn duel(first: Position, second: Position) -> Simulation {
+        let mut simulation = board(10, 10, 1);
+        simulation.set_snake_count(2);
+        simulation.reset(1);
+        simulation.snakes[0].body = vec![first];
+        simulation.snakes[0].direction = Direction::Right;
+        simulation.snakes[1].body = vec![second];
+        simulation.snakes[1].direction = Direction::Left;
+        simulation.foods.clear();
+        simulation.update_spatial_hash();
+        simulation
+    }
+
+    fn scripted_input(tick: u32) -> Option<Direction> {
+        const TURNS: [Direction; 4] = [Direction::Down, Direction::Left, Direction::Up, Direction::Right];
+        tick.is_multiple_of(3).then(|| TURNS[(tick / 3) as usize % TURNS.len()])
+    }
+
+    fn food_sequence(seed: u64) -> Vec<Position> {
+        let mut simulation = simulation(seed);
+        (0..50).map(|_| next_food(&mut simulation)).collect()
+    }
+
+    #[test]
+    fn same_seed_gives_same_food_sequence() {
+        assert_eq!(food_sequence(42), food_sequence(42));
+        assert_ne!(food_sequence(42), food_sequence(43));
+    }
+
+    #[test]
+    fn same_seed_and_inputs_give_same_game() {
+        let mut first = simulation(42);
+        let mut second = simulation(42);
+
+        for tick in 0..200 {
+            step(&mut first, scripted_input(tick));
+            step(&mut second, scripted_input(tick));
+            assert_eq!(first.foods(), second.foods());
+            assert_eq!(first.player().body, second.player().body);
+            assert_eq!(first.player().score, second.player().score);
+        }
+    }
+
+    #[test]
+    fn reset_restarts_the_food_sequence() {
+        let mut simulation = simulation(7);
+        let first: Vec<Position> = (0..10).map(|_| next_food(&mut simulation)).collect();
+
+        simulation.reset(7);
+        let again: Vec<Position> = (0..10).map(|_| next_food(&mut simulation)).collect();
+        assert_eq!(first, again);
+    }
+
+    #[test]
+    fn food_spawns_on_the_last_free_cell() {
+        let free = Position { x: 2, y: 1 };
+        for seed in 0..20 {
+            let mut simulation = board(3, 3, seed);
+            fill_board_except(&mut simulation, &[free]);
+            assert_eq!(next_food(&mut simulation), free);
+        }
+    }
+
+    #[test]
+    fn food_only_spawns_on_free_cells() {
+        let free = [Position { x: 0, y: 0 }, Position { x: 4, y: 3 }, Position { x: 2, y: 4 }];
+        for seed in 0..50 {
+            let mut simulation = board(5, 5, seed);
+            fill_board_except(&mut simulation, &free);
+            assert!(free.contains(&next_food(&mut simulation)));
+        }
+    }
+
+    #[test]
+    fn no_food_when_the_board_is_full() {
+        let mut simulation = board(3, 3, 1);
+        fill_board_except(&mut simulation, &[]);
+        simulation.foods.clear();
+        simulation.spawn_food(FoodKind::Normal);
+        assert!(simulation.foods().is_empty());
+    }
+
+    #[test]
+    fn head_on_collision_kills_both() {
+        let mut simulation = duel(Position { x: 2, y: 5 }, Position { x: 4, y: 5 });
+        let events = simulation.step(&[None, None]);
+
+        assert!(events.contains(&SimEvent::Died(0)));
+        assert!(events.contains(&SimEvent::Died(1)));
+        assert!(simulation.snakes().iter().all(|snake| !snake.alive));
+    }
+
+    #[test]
+    fn head_swap_kills_both() {
+        let mut simulation = duel(Position { x: 2, y: 5 }, Position { x: 3, y: 5 });
+        let events = simulation.step(&[None, None]);
+
+        assert!(events.contains(&SimEvent::Died(0)));
+        assert!(events.contains(&SimEvent::Died(1)));
+        assert!(simulation.snakes().iter().all(|snake| !snake.alive));
+    }
+}
@@ -1,6 +1,7 @@
 use std::collections::{HashMap, HashSet};
 use crate::position::Position;
 
+#[derive(Clone)]
 pub struct SpatialHash {
     cell_size: i32,
     grid: HashMap<(i32, i32), HashSet<Position>>,
@@ -20,7 +21,7 @@ impl SpatialHash {
 
     pub fn insert(&mut self, pos: Position) {
         let cell = (pos.x / self.cell_size, pos.y / self.cell_size);
-        self.grid.entry(cell).or_insert_with(HashSet::new).insert(pos);
+        self.grid.entry(cell).or_default().insert(pos);
     }
 
     pub fn contains(&self, pos: Position) -> bool {
@@ -31,4 +32,8 @@ impl SpatialHash {
             false
         }
     }
+
+    pub fn occupied(&self) -> usize {
+        self.grid.values().map(|positions| positions.len()).sum()
+    }
 }
\ No newline at end of file
@@ -0,0 +1,33 @@
+use std::env;
+use std::path::PathBuf;
+
+pub fn data_dir() -> PathBuf {
+    if let Some(dir) = env::var_os("ILON_DATA_DIR") {
+        return PathBuf::from(dir);
+    }
+
+    platform_data_dir()
+        .map(|dir| dir.join("ilon"))
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+pub fn replays_dir() -> PathBuf {
+    data_dir().join("replays")
+}
+
+#[cfg(target_os = "windows")]
+fn platform_data_dir() -> Option<PathBuf> {
+    env::var_os("APPDATA").map(PathBuf::from)
+}
+
+#[cfg(target_os = "macos")]
+fn platform_data_dir() -> Option<PathBuf> {
+    env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn platform_data_dir() -> Option<PathBuf> {
+    env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+}
@@ -1,29 +1,89 @@
 use macroquad::prelude::*;
+use crate::food::FoodKind;
+use crate::powerup::PowerUpKind;
 
 pub struct TextureCache {
     pub snake_head: Option<Texture2D>,
-    pub snake_body: Option<Texture2D>,
     pub food: Option<Texture2D>,
+    pub bonus_food: Option<Texture2D>,
+    pub shrink_food: Option<Texture2D>,
+    pub slow_food: Option<Texture2D>,
+    pub ghost_powerup: Option<Texture2D>,
+    pub magnet_powerup: Option<Texture2D>,
+    pub multiplier_powerup: Option<Texture2D>,
 }
 
 impl TextureCache {
     pub fn new() -> Self {
         Self {
             snake_head: None,
-            snake_body: None,
             food: None,
+            bonus_food: None,
+            shrink_food: None,
+            slow_food: None,
+            ghost_powerup: None,
+            magnet_powerup: None,
+            multiplier_powerup: None,
         }
     }
 
     pub async fn load_textures(&mut self) {
         self.snake_head = Some(Self::create_snake_head_texture().await);
-        self.snake_body = Some(Self::create_snake_body_texture().await);
-        self.food = Some(Self::create_food_texture().await);
+        self.food = Some(Self::create_food_texture(FoodKind::Normal).await);
+        self.bonus_food = Some(Self::create_food_texture(FoodKind::Bonus).await);
+        self.shrink_food = Some(Self::create_food_texture(FoodKind::Shrink).await);
+        self.slow_food = Some(Self::create_food_texture(FoodKind::Slow).await);
+        self.ghost_powerup = Some(Self::create_powerup_texture(PowerUpKind::Ghost).await);
+        self.magnet_powerup = Some(Self::create_powerup_texture(PowerUpKind::Magnet).await);
+        self.multiplier_powerup = Some(Self::create_powerup_texture(PowerUpKind::Multiplier).await);
+    }
+
+    pub fn food_texture(&self, kind: FoodKind) -> Option<&Texture2D> {
+        match kind {
+            FoodKind::Normal => self.food.as_ref(),
+            FoodKind::Bonus => self.bonus_food.as_ref(),
+            FoodKind::Shrink => self.shrink_food.as_ref(),
+            FoodKind::Slow => self.slow_food.as_ref(),
+        }
+    }
+
+    pub fn food_color(kind: FoodKind) -> Color {
+        match kind {
+            FoodKind::Normal => Color::from_rgba(255, 100, 100, 255),
+            FoodKind::Bonus => Color::from_rgba(255, 210, 60, 255),
+            FoodKind::Shrink => Color::from_rgba(170, 90, 255, 255),
+            FoodKind::Slow => Color::from_rgba(80, 180, 255, 255),
+        }
+    }
+
+    pub fn snake_colors(index: usize) -> (Color, Color) {
+        match index {
+            0 => (Color::from_rgba(50, 255, 50, 255), Color::from_rgba(0, 200, 0, 255)),
+            1 => (Color::from_rgba(90, 180, 255, 255), Color::from_rgba(30, 110, 230, 255)),
+            2 => (Color::from_rgba(255, 225, 70, 255), Color::from_rgba(215, 170, 0, 255)),
+            _ => (Color::from_rgba(255, 120, 220, 255), Color::from_rgba(205, 60, 175, 255)),
+        }
+    }
+
+    pub fn powerup_texture(&self, kind: PowerUpKind) -> Option<&Texture2D> {
+        match kind {
+            PowerUpKind::Ghost => self.ghost_powerup.as_ref(),
+            PowerUpKind::Magnet => self.magnet_powerup.as_ref(),
+            PowerUpKind::Multiplier => self.multiplier_powerup.as_ref(),
+        }
+    }
+
+    pub fn powerup_color(kind: PowerUpKind) -> Color {
+        match kind {
+            PowerUpKind::Ghost => Color::from_rgba(220, 220, 255, 255),
+            PowerUpKind::Magnet => Color::from_rgba(255, 80, 200, 255),
+            PowerUpKind::Multiplier => Color::from_rgba(255, 150, 40, 255),
+        }
     }
 
     async fn create_snake_head_texture() -> Texture2D {
         let size = 64;
-        let mut image = Image::gen_image_color(size, size, Color::from_rgba(50, 255, 50, 255));
+        let mut image = Image::gen_image_color(size, size, WHITE);
 
         for x in 40..50 {
             for y in 15..25 {
@@ -39,19 +99,32 @@ impl TextureCache {
         Texture2D::from_image(&image)
     }
 
-    async fn create_snake_body_texture() -> Texture2D {
+    async fn create_food_texture(kind: FoodKind) -> Texture2D {
         let size = 64;
-        let image = Image::gen_image_color(size, size, Color::from_rgba(0, 200, 0, 255));
+        let mut image = Image::gen_image_color(size, size, Self::food_color(kind));
+
+        for x in 10..20 {
+            for y in 10..20 {
+                image.set_pixel(x, y, WHITE);
+            }
+        }
+
         Texture2D::from_image(&image)
     }
 
-    async fn create_food_texture() -> Texture2D {
+    async fn create_powerup_texture(kind: PowerUpKind) -> Texture2D {
         let size = 64;
-        let mut image = Image::gen_image_color(size, size, Color::from_rgba(255, 100, 100, 255));
+        let half = size as i32 / 2;
+        let mut image = Image::gen_image_color(size, size, BLANK);
 
-        for x in 10..20 {
-            for y in 10..20 {
-                image.set_pixel(x, y, WHITE);
+        for x in 0..size as i32 {
+            for y in 0..size as i32 {
+                let distance = (x - half).abs() + (y - half).abs();
+                if distance < half - 6 {
+                    image.set_pixel(x as u32, y as u32, Self::powerup_color(kind));
+                } else if distance < half {
+                    image.set_pixel(x as u32, y as u32, WHITE);
+                }
             }
         }
 
@@ -0,0 +1,50 @@
+use macroquad::prelude::*;
+
+const BUTTON_HEIGHT: f32 = 40.0;
+const BUTTON_PADDING: f32 = 24.0;
+const BUTTON_GAP: f32 = 12.0;
+const FONT_SIZE: u16 = 22;
+
+pub struct Button<T> {
+    pub rect: Rect,
+    pub label: &'static str,
+    pub action: T,
+}
+
+pub fn button_row<T>(items: Vec<(&'static str, T)>, y: f32) -> Vec<Button<T>> {
+    let widths: Vec<f32> = items
+        .iter()
+        .map(|(label, _)| measure_text(label, None, FONT_SIZE, 1.0).width + BUTTON_PADDING * 2.0)
+        .collect();
+    let total = widths.iter().sum::<f32>() + BUTTON_GAP * items.len().saturating_sub(1) as f32;
+
+    let mut x = (screen_width() - total) / 2.0;
+    items
+        .into_iter()
+        .zip(widths)
+        .map(|((label, action), width)| {
+            let rect = Rect::new(x, y, width, BUTTON_HEIGHT);
+            x += width + BUTTON_GAP;
+            Button { rect, label, action }
+        })
+        .collect()
+}
+
+pub fn draw_buttons<T>(buttons: &[Button<T>]) {
+    let mouse = Vec2::from(mouse_position());
+    for button in buttons {
+        let hovered = button.rect.contains(mouse);
+        let fill = if hovered { Color::from_rgba(70, 90, 130, 230) } else { Color::from_rgba(40, 50, 75, 230) };
+        let rect = button.rect;
+        draw_rectangle(rect.x, rect.y, rect.w, rect.h, fill);
+        draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 2.0, if hovered { YELLOW } else { GRAY });
+
+        let dims = measure_text(button.label, None, FONT_SIZE, 1.0);
+        let text_y = rect.y + (rect.h + dims.offset_y) / 2.0;
+        draw_text(button.label, rect.x + BUTTON_PADDING, text_y, FONT_SIZE as f32, WHITE);
+    }
+}
+
+pub fn clicked<T: Copy>(buttons: &[Button<T>], position: Vec2) -> Option<T> {
+    buttons.iter().find(|button| button.rect.contains(position)).map(|button| button.action)
+}
@@ -0,0 +1,28 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WallMode {
+    Solid,
+    Wrap,
+}
+
+impl WallMode {
+    pub fn toggled(self) -> WallMode {
+        match self {
+            WallMode::Solid => WallMode::Wrap,
+            WallMode::Wrap => WallMode::Solid,
+        }
+    }
+
+    pub fn id(self) -> &'static str {
+        match self {
+            WallMode::Solid => "solid",
+            WallMode::Wrap => "wrap",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            WallMode::Solid => "Devorlar",
+            WallMode::Wrap => "Devorsiz",
+        }
+    }
+}